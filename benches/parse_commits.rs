@@ -0,0 +1,56 @@
+//! Speed of `parser::parse_commits`, the step `--jobs` spreads over threads,
+//! with one job and with one per core (at least two, so the parallel path always
+//! runs). Run with `cargo bench`.
+
+use std::hint::black_box;
+
+use chronicle::git_provider::CommitInfo;
+use chronicle::parser::{self, DEFAULT_COMMIT_PATTERN};
+use chrono::{TimeZone, Utc};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use regex::Regex;
+
+const COMMIT_COUNT: usize = 100_000;
+
+// Commits of every kind the parser tells apart: scopes, breaking changes, pull
+// request numbers, bodies with list items and closing footers
+fn commits(count: usize) -> Vec<CommitInfo> {
+    let messages = [
+        "feat(api): add endpoint (#12)",
+        "fix(ui, core)!: handle empty input\n\nBREAKING CHANGE: input is required",
+        "docs: describe the options\n\n- flags\n- environment\n\nCloses #4, #5",
+        "refactor: split the parser",
+        "Merge branch 'main' into feature",
+        "chore: bump dependencies",
+    ];
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+
+    (0..count)
+        .map(|index| CommitInfo {
+            id: format!("{:040x}", index),
+            message: format!("{} {}", messages[index % messages.len()], index),
+            timestamp: (start + chrono::Duration::minutes(index as i64)).fixed_offset(),
+            signed: false,
+            author: format!("Author {}", index % 7),
+        })
+        .collect()
+}
+
+fn parse_commits(c: &mut Criterion) {
+    let regex = Regex::new(DEFAULT_COMMIT_PATTERN).unwrap();
+    let commits = commits(COMMIT_COUNT);
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+
+    let mut group = c.benchmark_group("parse_commits");
+    group.throughput(Throughput::Elements(COMMIT_COUNT as u64));
+    group.sample_size(10);
+    for jobs in [1, cores.max(2)] {
+        group.bench_with_input(BenchmarkId::from_parameter(jobs), &jobs, |b, &jobs| {
+            b.iter(|| parser::parse_commits(black_box(&commits), &regex, jobs))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_commits);
+criterion_main!(benches);
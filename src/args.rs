@@ -1,39 +1,517 @@
-use crate::{OutputFormat, SortOrder};
-use clap::Parser;
+use crate::{
+    DateGrouping, GroupBy, Layout, LineEnding, OutputFormat, Prerelease, ScopeSort, SortOrder,
+    Theme, VersionDateSource,
+    changelog_generator::{CommitTypeDefault, DEFAULT_COLLAPSE_SUMMARY, ScopeCollapse},
+    commit_template::{self, DEFAULT_COMMIT_TEMPLATE},
+    commit_type::{CommitType, TypeMerge},
+    stdin_provider::DEFAULT_LOG_FORMAT,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{ArgGroup, Parser};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
-    about = "Generate a changelog from git commit history"
+    about = "Generate a changelog from git commit history",
+    group(ArgGroup::new("scoped").args(["group_by_scope", "layout"])),
+    group(ArgGroup::new("uses_now").args(["relative_dates", "footer_timestamp"]).multiple(true))
 )]
 pub struct Args {
-    /// Path to the git repository
+    /// Path to the git repository, or - to read `git log --format=<STDIN_FORMAT>` output from stdin
     #[arg(short, long, default_value = ".")]
     pub repository: PathBuf,
 
-    /// Output file path for the changelog
-    #[arg(short, long, default_value = "CHANGELOG.md")]
-    pub output: PathBuf,
+    /// Pretty format of the git log output read with -r -: %H, %cI or %aI, %an, %G? and %B or %s separated by %x1f, ending with %x1e
+    #[arg(long, value_name = "FORMAT", default_value = DEFAULT_LOG_FORMAT)]
+    pub stdin_format: String,
 
-    /// Title for the changelog
-    #[arg(short, long, default_value = "Changelog")]
-    pub title: String,
+    /// File of version tags for -r -, one "NAME COMMIT [DATE]" line each
+    #[arg(long, value_name = "PATH")]
+    pub stdin_tags: Option<PathBuf>,
 
-    /// Format for the changelog
-    #[arg(short, long, value_enum, default_value_t = OutputFormat::Markdown)]
-    pub format: OutputFormat,
+    /// Render the versions of an earlier -f ndjson output file instead of reading a repository
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdin_tags", "cache"])]
+    pub import: Option<PathBuf>,
+
+    /// Output file path for the changelog, or - for stdout (repeat to give each format its own path); with --latest or --unreleased-only, {version} and {date} are filled in [default: CHANGELOG.md, stdout for slack]
+    #[arg(short, long)]
+    pub output: Vec<PathBuf>,
+
+    /// Write to the exact --output paths instead of fixing their extension to match the format
+    #[arg(long)]
+    pub no_extension_fixup: bool,
+
+    /// Directory to write every format into, named after the title (e.g. CHANGELOG.md)
+    #[arg(long, value_name = "DIR", conflicts_with = "output")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Write each version to its own file in --output-dir (e.g. v1.0.0.md), along with an index.json manifest
+    #[arg(long, requires = "output_dir", conflicts_with = "check")]
+    pub split: bool,
+
+    /// Write each commit type's commits across all versions to its own file in --output-dir (e.g. features.md, bug-fixes.md)
+    #[arg(long, requires = "output_dir", conflicts_with_all = ["check", "split"])]
+    pub per_type_files: bool,
+
+    /// Write the changelog even when there are no commits, instead of exiting with code 2
+    #[arg(long)]
+    pub allow_empty: bool,
+
+    /// Only render the most recent released version, without title, preamble or footer (release notes for the latest tag)
+    #[arg(long)]
+    pub latest: bool,
+
+    /// Only render the unreleased changes, without title, preamble or footer; exits with 2 if there are none
+    #[arg(long, conflicts_with = "latest")]
+    pub unreleased_only: bool,
+
+    /// Only render the N newest versions; the unreleased section comes on top unless --count-unreleased
+    #[arg(long, value_name = "N", conflicts_with_all = ["latest", "unreleased_only"])]
+    pub max_versions: Option<usize>,
+
+    /// Count the unreleased section as one of the --max-versions
+    #[arg(long, requires = "max_versions")]
+    pub count_unreleased: bool,
+
+    /// Compare the generated changelog with the existing output files instead of writing them; exits with 1 and prints a diff if they differ
+    #[arg(long)]
+    pub check: bool,
+
+    /// Only add the versions released since the newest one in the existing markdown --output file, leaving the rest of it as it is; exits with 2 if there are none
+    #[arg(long, conflicts_with_all = ["check", "split", "per_type_files", "gzip", "latest", "unreleased_only", "max_versions"])]
+    pub incremental: bool,
+
+    /// Title for the changelog; --check reuses the existing file's "# " heading unless given [default: Changelog]
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Format for the changelog (repeat to render several formats in one run)
+    #[arg(short, long, value_enum, default_values_t = [OutputFormat::Markdown])]
+    pub format: Vec<OutputFormat>,
 
     /// Sort order for commits
     #[arg(short, long, value_enum, default_value_t = SortOrder::Newest)]
     pub sort_order: SortOrder,
 
+    /// List commits oldest first within each version, independent of --sort-order
+    #[arg(long)]
+    pub reverse_within_version: bool,
+
     /// Custom regex pattern for commit messages
     #[arg(long)]
     pub commit_pattern: Option<String>,
 
+    /// List commits whose pattern matched with an empty message (e.g. a bare "feat:") under Other with their whole subject
+    #[arg(long)]
+    pub group_empty_as_other: bool,
+
+    /// List each "- " item in a commit body as a bullet of its own, with the commit's type and scope, instead of the subject
+    #[arg(long)]
+    pub expand_body_bullets: bool,
+
+    /// Link the "(#N)" pull request numbers of squash-merged commits to the remote's pull request pages
+    #[arg(long)]
+    pub link_prs: bool,
+
     /// Custom regex pattern for version tags
     #[arg(long)]
     pub version_pattern: Option<String>,
+
+    /// Keep prerelease versions (e.g. v1.0.0-rc.1), leave them out with their commits going to the next stable version, or show only them
+    #[arg(long, value_enum, default_value_t = Prerelease::Include)]
+    pub prerelease: Prerelease,
+
+    /// List the commits of prerelease versions under the stable version released after them instead of in sections of their own
+    #[arg(long)]
+    pub group_prereleases_under_stable: bool,
+
+    /// Date versions by their annotated tag's tagger or by the tagged commit's committer (lightweight tags always use the committer)
+    #[arg(long, value_enum, value_name = "SOURCE", default_value_t = VersionDateSource::Tagger)]
+    pub version_date_source: VersionDateSource,
+
+    /// Name of a moving tag, e.g. nightly, that never marks a version; its commits stay unreleased (repeatable)
+    #[arg(long, value_name = "NAME")]
+    pub rolling_tag: Vec<String>,
+
+    /// Put the commits older than the earliest tag in a version of their own, named 0.1.0 unless NAME is given
+    #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "0.1.0")]
+    pub initial_version: Option<String>,
+
+    /// Display versions by the version pattern's first capture group, e.g. 1.2.3 for tag v1.2.3
+    #[arg(long)]
+    pub normalize_versions: bool,
+
+    /// Only include commits with this scope (repeatable, case-insensitive; "none" matches commits without a scope)
+    #[arg(long, value_name = "SCOPE")]
+    pub scope: Vec<String>,
+
+    /// Only include commits of this type, e.g. feat or fix (repeatable; "other" for unmatched commits)
+    #[arg(long, value_name = "TYPE", value_parser = parse_commit_type, conflicts_with = "exclude_type")]
+    pub include_type: Vec<CommitType>,
+
+    /// Leave out commits of this type, e.g. chore (repeatable; "other" for unmatched commits)
+    #[arg(long, value_name = "TYPE", value_parser = parse_commit_type)]
+    pub exclude_type: Vec<CommitType>,
+
+    /// Type to list commits of no known type under, e.g. chore, or "drop" to leave them out
+    #[arg(long, value_name = "TYPE", default_value = "other", value_parser = parse_commit_type_default)]
+    pub commit_type_default: CommitTypeDefault,
+
+    /// Only show the unreleased section if it has a commit of one of these types, e.g. feat,fix
+    #[arg(long, value_name = "TYPES", value_parser = parse_commit_type, value_delimiter = ',')]
+    pub unreleased_requires: Vec<CommitType>,
+
+    /// File of regexes for commit subjects to leave out [default: <REPOSITORY>/.chronicleignore]
+    #[arg(long, value_name = "PATH")]
+    pub ignore_file: Option<PathBuf>,
+
+    /// Leave out commits whose subject matches this regex, e.g. "^Merge" (repeatable, added to the ignore file's patterns)
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_message: Vec<String>,
+
+    /// Group commits by scope within each type section
+    #[arg(long)]
+    pub group_by_scope: bool,
+
+    /// Append the number of commits listed in each type section to its heading, e.g. "Features (5)"
+    #[arg(long)]
+    pub section_counts: bool,
+
+    /// Heading for commits without a scope when grouping by scope or with --layout scope-first
+    #[arg(
+        long,
+        value_name = "LABEL",
+        default_value = "general",
+        requires = "scoped"
+    )]
+    pub no_scope_label: String,
+
+    /// List the scopes with fewer than N commits in a version together in one group, last
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), requires = "scoped")]
+    pub min_scope_size: Option<u64>,
+
+    /// Heading of the group --min-scope-size folds small scopes into
+    #[arg(
+        long,
+        value_name = "LABEL",
+        default_value = "other",
+        requires = "min_scope_size"
+    )]
+    pub small_scope_label: String,
+
+    /// Order of scope groups: alphabetical, or by number of commits (most first)
+    #[arg(long, value_enum, default_value_t = ScopeSort::Alpha, requires = "scoped")]
+    pub scope_sort: ScopeSort,
+
+    /// Group path-like scopes such as api/v2 by their first N segments (0 for the whole scope)
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "scoped")]
+    pub scope_depth: usize,
+
+    /// Section each version by commit type, or by author (most commits first) with bullets in type order
+    #[arg(long, value_enum, default_value_t = GroupBy::Type, conflicts_with = "group_by_scope")]
+    pub group_by: GroupBy,
+
+    /// Nest scope groups inside type sections, or give each scope its own section with the type sections inside it (markdown and HTML)
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Layout::TypeFirst,
+        conflicts_with_all = ["group_by_scope", "group_by"]
+    )]
+    pub layout: Layout,
+
+    /// Group the unreleased section's commits by the day or week they were committed, newest first, with the type sections under each date
+    #[arg(long, value_enum, value_name = "PERIOD", conflicts_with_all = ["group_by", "scoped"])]
+    pub group_unreleased_by_date: Option<DateGrouping>,
+
+    /// Template for markdown bullets with {scope}, {message}, {hash}, {short_hash}, {url}, {type}, {date} and {author}; text in {?...} is dropped when a placeholder in it is empty
+    #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_COMMIT_TEMPLATE, value_parser = parse_commit_template)]
+    pub commit_template: String,
+
+    /// Render output through this Tera template file instead of the built-in layouts (needs the template feature)
+    #[arg(long, value_name = "PATH")]
+    pub template: Option<PathBuf>,
+
+    /// Only process the N most recent commits
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Only process the commits after the one TAG points at, up to HEAD
+    #[arg(long, value_name = "TAG")]
+    pub since_tag: Option<String>,
+
+    /// Only process the commits after the most recent version tag (the changes since the last release)
+    #[arg(long, conflicts_with = "since_tag")]
+    pub since_last_tag: bool,
+
+    /// Only process the commits of HEAD that REF doesn't have, from their merge base on (a pull request's changes)
+    #[arg(long, value_name = "REF", conflicts_with_all = ["since_tag", "since_last_tag"])]
+    pub base: Option<String>,
+
+    /// Show files changed and lines added/removed under each version heading; diffs every commit, so slower on large histories
+    #[arg(long)]
+    pub diffstat: bool,
+
+    /// List the top-level directories and files each commit changed under its bullet (markdown and HTML); diffs every commit, so slower on large histories
+    #[arg(long)]
+    pub show_paths: bool,
+
+    /// Most paths listed under a bullet with --show-paths; the rest are counted
+    #[arg(long, value_name = "N", default_value_t = 5, requires = "show_paths")]
+    pub max_paths: usize,
+
+    /// Number of threads to parse commits on (with the parallel feature); the output is the same for any number
+    #[arg(short, long, value_name = "N", default_value = "1")]
+    pub jobs: NonZeroUsize,
+
+    /// Cache parsed commits between runs
+    #[arg(long)]
+    pub cache: bool,
+
+    /// Directory for the commit cache [default: <REPOSITORY>/.git/chronicle-cache]
+    #[arg(long, value_name = "DIR", requires = "cache")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Leave out the section of commits that don't match the commit pattern
+    #[arg(long, conflicts_with = "rename_other")]
+    pub hide_other: bool,
+
+    /// Heading for the section of commits that don't match the commit pattern [default: Miscellaneous Tasks]
+    #[arg(long, value_name = "HEADING")]
+    pub rename_other: Option<String>,
+
+    /// Language of the section headings: de, es or fr (e.g. es or es-MX); others stay in English
+    #[arg(long, value_name = "CODE")]
+    pub locale: Option<String>,
+
+    /// Render several commit types in one section under a heading, e.g. "build,ci,chore=Maintenance" (repeatable)
+    #[arg(long, value_name = "TYPES=HEADING", value_parser = parse_type_merge)]
+    pub merge_types: Vec<TypeMerge>,
+
+    /// List a scope's commits in each section as one summary bullet, e.g. "deps=Updated {count} dependencies"; {count} is the number of commits [default summary: "{count} changes"] (repeatable)
+    #[arg(long, value_name = "SCOPE[=SUMMARY]", value_parser = parse_scope_collapse)]
+    pub collapse_scope: Vec<ScopeCollapse>,
+
+    /// Strip the emoji from section headings, e.g. "🚀 Features" becomes "Features"
+    #[arg(long)]
+    pub no_emoji: bool,
+
+    /// Start the markdown changelog with a "Breaking Changes" section listing every breaking commit
+    #[arg(long)]
+    pub breaking_summary: bool,
+
+    /// List issues closed by each version's commits (Closes/Fixes/Resolves #N footers) in a "Closed Issues" section
+    #[arg(long)]
+    pub closed_issues: bool,
+
+    /// Add empty versions for tags whose commit is not in the walked history
+    #[arg(long)]
+    pub include_unreachable_tags: bool,
+
+    /// Fail on a shallow clone instead of writing a changelog of its truncated history
+    #[arg(long)]
+    pub require_full_history: bool,
+
+    /// Mark signed commits with a lock (signatures are detected, not verified)
+    #[arg(long)]
+    pub show_signatures: bool,
+
+    /// Append each commit's date to its bullet (markdown and HTML), formatted like version dates
+    #[arg(long)]
+    pub show_commit_dates: bool,
+
+    /// Cut bullet messages longer than N characters down at a word boundary, ending with "…"; markdown and HTML show the full message as a tooltip
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_subject_length: Option<u64>,
+
+    /// Escape markdown characters such as * and _ in scopes and messages
+    #[arg(long)]
+    pub escape_markdown: bool,
+
+    /// Print diagnostics about the generated changelog to stderr
+    #[arg(short, long, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Suppress all non-error output
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Gzip each output, adding .gz to file names (e.g. CHANGELOG.md.gz); with -o - the compressed bytes go to stdout
+    #[arg(long, conflicts_with_all = ["check", "split", "per_type_files"])]
+    pub gzip: bool,
+
+    /// Don't color markdown written to a terminal with -o - (also set by the NO_COLOR environment variable)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Page output written to a terminal with -o - through $PAGER (less by default); the default
+    #[arg(long, overrides_with = "no_pager")]
+    pub pager: bool,
+
+    /// Write output to a terminal with -o - directly instead of through a pager
+    #[arg(long, overrides_with = "pager")]
+    pub no_pager: bool,
+
+    /// Format for version dates (chrono strftime syntax)
+    #[arg(long, default_value = "%Y-%m-%d")]
+    pub date_format: String,
+
+    /// Show dates in UTC instead of the timezone they were recorded in
+    #[arg(long)]
+    pub utc: bool,
+
+    /// Show version dates relative to now, e.g. "3 months ago"
+    #[arg(long)]
+    pub relative_dates: bool,
+
+    /// Date used as now for relative dates and the footer timestamp (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_name = "DATE", value_parser = parse_date, requires = "uses_now")]
+    pub reference_date: Option<DateTime<Utc>>,
+
+    /// Color theme for HTML output
+    #[arg(long, value_enum, default_value_t = Theme::Light)]
+    pub theme: Theme,
+
+    /// HTML heading level of the title; versions and type sections follow one level deeper each
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=6))]
+    pub heading_base: u8,
+
+    /// Write only the versions for embedding into another document: HTML without the <!DOCTYPE>, <head> and <body> chrome, markdown without the title, preamble and footer
+    #[arg(long)]
+    pub fragment: bool,
+
+    /// Write compact HTML without indentation and stylesheet whitespace
+    #[arg(long)]
+    pub minify: bool,
+
+    /// Line ending of the written files (NDJSON always uses LF)
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    pub line_ending: LineEnding,
+
+    /// Start the written files with a UTF-8 byte order mark, for tools that need one (not NDJSON)
+    #[arg(long)]
+    pub bom: bool,
+
+    /// Link target for HTML version headings; {remote} is the repository's web URL, {repo} its path on the host and {tag} the tag name [default: {remote}/releases/tag/{tag}]
+    #[arg(long, value_name = "TEMPLATE")]
+    pub tag_url_template: Option<String>,
+
+    /// Link target for commits, the {url} of --commit-template and --template; {hash} is the commit id, {remote} the repository's web URL and {repo} its path on the host [default: {remote}/commit/{hash}]
+    #[arg(long, value_name = "TEMPLATE")]
+    pub commit_url_template: Option<String>,
+
+    /// Link target for author headings with --group-by author, e.g. https://github.com/{login}; needs --author-logins
+    #[arg(long, value_name = "TEMPLATE", requires = "author_logins")]
+    pub author_url_template: Option<String>,
+
+    /// File mapping commit author names to logins, one "Author Name = login" per line
+    #[arg(long, value_name = "PATH", requires = "author_url_template")]
+    pub author_logins: Option<PathBuf>,
+
+    /// File of one-line version summaries shown in italics under version headings, one "v1.2.0" = "Summary" per line [default: <REPOSITORY>/chronicle-summaries.toml]
+    #[arg(long, value_name = "PATH")]
+    pub summaries: Option<PathBuf>,
+
+    /// Remote whose web URL links point at [default: origin, or else the first remote]
+    #[arg(long, value_name = "NAME")]
+    pub remote: Option<String>,
+
+    /// Omit the "generated by chronicle" footer
+    #[arg(long)]
+    pub no_footer: bool,
+
+    /// Include the generation date in the footer
+    #[arg(long, conflicts_with = "no_footer")]
+    pub footer_timestamp: bool,
+
+    /// Record the commit the changelog was generated from in the footer, as git describe --tags names it
+    #[arg(long, conflicts_with = "no_footer")]
+    pub describe_footer: bool,
+
+    /// Marker text for the markdown footer comment [default: generated by chronicle]
+    #[arg(long, value_name = "MARKER")]
+    pub footer_marker: Option<String>,
+}
+
+// Accepts a conventional commit prefix, or "other" for the unmatched bucket
+fn parse_commit_type(value: &str) -> Result<CommitType, String> {
+    CommitType::all()
+        .iter()
+        .find(|commit_type| commit_type.as_str() == value)
+        .cloned()
+        .ok_or_else(|| {
+            let known: Vec<&str> = CommitType::all().iter().map(CommitType::as_str).collect();
+            format!(
+                "unknown commit type '{}', expected one of {}",
+                value,
+                known.join(", ")
+            )
+        })
+}
+
+// Accepts a commit type, or "drop" to leave commits of no known type out
+fn parse_commit_type_default(value: &str) -> Result<CommitTypeDefault, String> {
+    if value == "drop" {
+        Ok(CommitTypeDefault::Drop)
+    } else {
+        parse_commit_type(value).map(CommitTypeDefault::Type)
+    }
+}
+
+// Accepts comma-separated commit types and the heading of their section,
+// e.g. "build,ci,chore=Maintenance"
+fn parse_type_merge(value: &str) -> Result<TypeMerge, String> {
+    let (types, heading) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected TYPES=HEADING, got '{}'", value))?;
+    let heading = heading.trim();
+    if heading.is_empty() {
+        return Err(format!("missing heading in '{}'", value));
+    }
+
+    Ok(TypeMerge {
+        types: types
+            .split(',')
+            .map(|commit_type| parse_commit_type(commit_type.trim()))
+            .collect::<Result<_, _>>()?,
+        heading: heading.to_string(),
+    })
+}
+
+// Accepts a scope, optionally followed by the summary its bullet reads,
+// e.g. "deps=Updated {count} dependencies"
+fn parse_scope_collapse(value: &str) -> Result<ScopeCollapse, String> {
+    let (scope, summary) = value
+        .split_once('=')
+        .map_or((value, DEFAULT_COLLAPSE_SUMMARY), |(scope, summary)| {
+            (scope, summary.trim())
+        });
+    let scope = scope.trim();
+    if scope.is_empty() || summary.is_empty() {
+        return Err(format!("expected SCOPE or SCOPE=SUMMARY, got '{}'", value));
+    }
+
+    Ok(ScopeCollapse {
+        scope: scope.to_string(),
+        summary: summary.to_string(),
+    })
+}
+
+fn parse_commit_template(value: &str) -> Result<String, String> {
+    commit_template::validate(value)?;
+    Ok(value.to_string())
+}
+
+// Accepts either a plain date (midnight UTC) or a full RFC 3339 timestamp
+fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD or RFC 3339", value))
 }
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Read the author logins listed in a mapping file
+pub fn load(path: &Path) -> io::Result<HashMap<String, String>> {
+    parse(&fs::read_to_string(path)?)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// One `Author Name = login` per line, mapping a commit author's name to the
+/// login their profile URL is built from. Blank lines and lines starting with `#`
+/// are skipped.
+pub fn parse(contents: &str) -> Result<HashMap<String, String>, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.rsplit_once('=') {
+            Some((name, login)) if !name.trim().is_empty() && !login.trim().is_empty() => {
+                Ok((name.trim().to_string(), login.trim().to_string()))
+            }
+            _ => Err(format!("expected 'Author Name = login', got '{}'", line)),
+        })
+        .collect()
+}
@@ -1,321 +1,2923 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{LineWriter, Write};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc, Weekday};
 use regex::Regex;
 
 use crate::{
-    SortOrder,
-    commit_type::CommitType,
-    git_provider::{GitProvider, Result},
-    git2_provider::Git2Provider,
+    DateGrouping, GroupBy, Layout, LineEnding, OutputFormat, Prerelease, ScopeSort, SortOrder,
+    Theme, VersionDateSource,
+    commit_cache::{CachedCommit, CommitCache},
+    commit_template,
+    commit_type::{CommitType, TypeMerge},
+    document_template::{Context, Template, Value},
+    error::{ChronicleError, Result},
+    git_provider::{CommitInfo, DiffStat, GitError, GitProvider, TagInfo},
+    json,
+    line_ending::LineEndingWriter,
     parsed_commit::ParsedCommit,
-    version,
+    parser, remote, version,
 };
 
+pub const DEFAULT_VERSION_PATTERN: &str = r"^v?(\d+\.\d+\.\d+)$";
+pub use crate::parser::DEFAULT_COMMIT_PATTERN;
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Scope filter value that selects commits without a scope
+pub const NO_SCOPE: &str = "none";
+
+/// Default heading for scope-less commits when grouping by scope
+pub const NO_SCOPE_LABEL: &str = "general";
+
+/// Default heading of the group that scopes below the minimum size are folded into
+pub const SMALL_SCOPE_LABEL: &str = "other";
+
+/// Section heading for commits without an author name when grouping by author
+pub const UNKNOWN_AUTHOR: &str = "Unknown";
+
+/// Marker written into the markdown footer comment. Anything that needs to find
+/// where generated content ends should look for this string.
+pub const FOOTER_MARKER: &str = "generated by chronicle";
+
+// Name and date of the tag a version starts at
+type VersionTag = (String, Option<DateTime<FixedOffset>>);
+
+/// A parsed commit along with the version it was released in
+#[derive(Debug)]
+pub struct VersionedCommit {
+    /// Tag name, or "unreleased" for commits newer than the latest tag
+    pub version: String,
+    pub version_date: Option<DateTime<FixedOffset>>,
+    pub commit: ParsedCommit,
+}
+
+/// Where the commit walk stops, for a changelog of the changes since a release
+#[derive(Debug, Clone, PartialEq)]
+pub enum Since {
+    /// After the commit the named tag points at
+    Tag(String),
+    /// After the most recent tag matching the version pattern
+    LastTag,
+    /// After the merge base of HEAD and a branch, tag or commit, leaving the
+    /// commits unique to the current branch
+    Base(String),
+}
+
+/// Where commits of no known type go, i.e. those the commit pattern doesn't match
+/// or whose type isn't a conventional one
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitTypeDefault {
+    /// Listed under this type, `Other` by default
+    Type(CommitType),
+    /// Left out of the changelog
+    Drop,
+}
+
+/// A scope whose commits are listed as one summary bullet per section, such as
+/// the `deps` scope of dependency bumps
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeCollapse {
+    pub scope: String,
+    /// Text of the bullet, with `{count}` replaced by the number of commits
+    pub summary: String,
+}
+
+/// Summary bullet of a collapsed scope when none is given
+pub const DEFAULT_COLLAPSE_SUMMARY: &str = "{count} changes";
+
+/// Heading of the section listing issues closed by a version's commits
+pub const CLOSED_ISSUES_HEADING: &str = "✅ Closed Issues";
+
+/// Length of the `{short_hash}` commit template placeholder
+const SHORT_HASH_LENGTH: usize = 7;
+
+/// Appended to the bullets of signed commits with `show_signatures`
+pub const SIGNED_MARKER: &str = " 🔒";
+
 pub struct ChangelogGenerator<P: GitProvider> {
     pub git: P,
     pub version_regex: Regex,
     pub commit_regex: Regex,
     pub sort_order: SortOrder,
+    pub date_format: String,
+    pub theme: Theme,
+    pub footer: bool,
+    pub footer_timestamp: bool,
+    /// HEAD as `git describe` names it, recorded in the footer
+    pub footer_describe: Option<String>,
+    pub footer_marker: String,
+    pub scopes: Vec<String>,
+    pub include_types: Vec<CommitType>,
+    pub exclude_types: Vec<CommitType>,
+    pub unreleased_requires: Vec<CommitType>,
+    pub group_by_scope: bool,
+    /// Append the number of commits to each section heading
+    pub section_counts: bool,
+    pub no_scope_label: String,
+    /// Fold scopes with fewer commits than this in a version into one group
+    pub min_scope_size: Option<usize>,
+    /// Heading of the group the scopes below `min_scope_size` are folded into
+    pub small_scope_label: String,
+    pub scope_sort: ScopeSort,
+    pub scope_depth: usize,
+    pub group_by: GroupBy,
+    pub layout: Layout,
+    pub commit_template: String,
+    pub document_template: Option<Template>,
+    pub closed_issues: bool,
+    pub empty_as_other: bool,
+    pub link_prs: bool,
+    pub relative_dates: bool,
+    /// The current time, for relative dates and the footer timestamp
+    pub now: DateTime<Utc>,
+    pub include_unreachable_tags: bool,
+    /// Only walk the commits after this release
+    pub since: Option<Since>,
+    pub prerelease: Prerelease,
+    /// List the commits of prerelease versions under the stable version that
+    /// follows them
+    pub group_prereleases_under_stable: bool,
+    pub version_date_source: VersionDateSource,
+    /// Moving tags such as `nightly` that never mark a version
+    pub rolling_tags: Vec<String>,
+    /// Name for the commits older than the earliest version tag, which otherwise
+    /// belong to that tag's version
+    pub initial_version: Option<String>,
+    pub limit: Option<usize>,
+    pub jobs: usize,
+    pub diffstat: bool,
+    pub show_paths: bool,
+    pub max_paths: usize,
+    /// List the items of a `- ` list in a commit body instead of its subject
+    pub expand_body_bullets: bool,
+    pub reverse_within_version: bool,
+    pub escape_markdown: bool,
+    pub ignore_regexes: Vec<Regex>,
+    pub cache: Option<CommitCache>,
+    pub show_signatures: bool,
+    /// Append each commit's date to its bullet
+    pub show_commit_dates: bool,
+    /// Cut bullet messages longer than this many characters down to it
+    pub max_subject_length: Option<usize>,
+    pub commit_type_default: CommitTypeDefault,
+    /// Subdivide the unreleased version by the day or week of its commits
+    pub unreleased_date_grouping: Option<DateGrouping>,
+    pub utc: bool,
+    pub tag_url_template: String,
+    pub commit_url_template: String,
+    pub author_url_template: Option<String>,
+    /// Logins of commit authors by name, for `author_url_template`
+    pub author_logins: HashMap<String, String>,
+    /// One-line summaries shown under the headings of the versions they name
+    pub summaries: HashMap<String, String>,
+    pub remote_url: Option<String>,
+    pub hide_other: bool,
+    pub other_heading: Option<String>,
+    /// Translated section headings by commit type, from `--locale`; other types
+    /// keep their English heading
+    pub headings: HashMap<CommitType, String>,
+    pub type_merges: Vec<TypeMerge>,
+    /// Scopes whose commits are summed up in one bullet per section
+    pub scope_collapses: Vec<ScopeCollapse>,
+    pub emoji: bool,
+    pub breaking_summary: bool,
+    pub normalize_versions: bool,
+    pub notes_only: bool,
+    pub heading_base: u8,
+    /// Leave out what surrounds the versions for embedding into another
+    /// document: the HTML page chrome, and markdown's title, preamble and footer
+    pub fragment: bool,
+    pub minify: bool,
+    pub line_ending: LineEnding,
+    /// Start the output with a UTF-8 byte order mark
+    pub bom: bool,
 }
 
-impl ChangelogGenerator<Git2Provider> {
-    pub fn new(repo_path: &Path, sort_order: SortOrder) -> Result<Self> {
-        let git = Git2Provider::open(repo_path)?;
-        let version_regex = Regex::new(r"^v?(\d+\.\d+\.\d+)$").unwrap();
-        let commit_regex =
-            Regex::new(r"^(?P<type>\w+)(?:\((?P<scope>.+)\))?:\s(?P<message>.+)$").unwrap();
-
-        Ok(Self {
-            git,
-            version_regex,
-            commit_regex,
-            sort_order,
-        })
+impl<P: GitProvider> ChangelogGenerator<P> {
+    // Version (and optional date) shared by the markdown and HTML footers, or None
+    // when the footer is disabled
+    fn footer_text(&self) -> Option<String> {
+        if !self.footer || self.notes_only {
+            return None;
+        }
+
+        let mut text = env!("CARGO_PKG_VERSION").to_string();
+        if let Some(describe) = &self.footer_describe {
+            text.push_str(&format!(" at {}", describe));
+        }
+        if self.footer_timestamp {
+            text.push_str(&format!(" on {}", self.now.format(&self.date_format)));
+        }
+
+        Some(text)
     }
 
-    pub fn with_patterns(
-        repo_path: &Path,
-        version_pattern: Option<&str>,
-        commit_pattern: Option<&str>,
-        sort_order: SortOrder,
-    ) -> Result<Self> {
-        let git = Git2Provider::open(repo_path)?;
-        let version_regex = version_pattern
-            .map(|pattern| Regex::new(pattern).unwrap())
-            .unwrap_or_else(|| Regex::new(r"^v?(\d+\.\d+\.\d+)$").unwrap());
-        let commit_regex = commit_pattern
-            .map(|pattern| Regex::new(pattern).unwrap())
-            .unwrap_or_else(|| {
-                Regex::new(r"^(?P<type>\w+)(?:\((?P<scope>.+)\))?:\s(?P<message>.+)$").unwrap()
-            });
+    // Formats a version date, either absolute with `date_format` in the date's own
+    // offset (or UTC with `utc`), or relative to `now`
+    fn format_date(&self, date: DateTime<FixedOffset>) -> String {
+        if self.relative_dates {
+            relative_date(date.with_timezone(&Utc), self.now)
+        } else if self.utc {
+            date.with_timezone(&Utc)
+                .format(&self.date_format)
+                .to_string()
+        } else {
+            date.format(&self.date_format).to_string()
+        }
+    }
 
-        Ok(Self {
-            git,
-            version_regex,
-            commit_regex,
-            sort_order,
-        })
+    // Scope or message text as it should appear in the markdown output
+    fn markdown_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.escape_markdown {
+            Cow::Owned(escape_markdown(text))
+        } else {
+            Cow::Borrowed(text)
+        }
     }
-}
 
-impl<P: GitProvider> ChangelogGenerator<P> {
-    pub fn parse_commit(&self, commit_info: &crate::git_provider::CommitInfo) -> ParsedCommit {
-        let message = commit_info.message.lines().next().unwrap_or("").trim();
-        let id = commit_info.id.clone();
-        let timestamp = commit_info.timestamp;
-
-        if let Some(captures) = self.commit_regex.captures(message) {
-            let commit_type =
-                CommitType::from_prefix(captures.name("type").map_or("", |m| m.as_str()));
-            let scope = captures.name("scope").map(|m| m.as_str().to_string());
-            let message = captures
-                .name("message")
-                .map_or("", |m| m.as_str())
-                .to_string();
-
-            ParsedCommit {
-                id,
-                commit_type,
-                scope,
-                message,
-                timestamp,
-            }
+    // Suffix appended to a commit's bullet when signatures are shown
+    fn signature_marker(&self, commit: &ParsedCommit) -> &'static str {
+        if self.show_signatures && commit.signed {
+            SIGNED_MARKER
         } else {
-            ParsedCommit {
-                id,
-                commit_type: CommitType::Other,
-                scope: None,
-                message: message.to_string(),
-                timestamp,
-            }
+            ""
         }
     }
 
-    pub fn generate_changelog(&self) -> Result<Vec<version::Version>> {
-        let mut versions: Vec<version::Version> = Vec::new();
-        let mut current_version = version::Version {
-            name: "unreleased".to_string(),
-            date: None,
-            commits_by_type: HashMap::new(),
+    // Suffix appended to a commit's markdown bullet when commit dates are shown
+    fn markdown_commit_date(&self, commit: &ParsedCommit) -> String {
+        if self.show_commit_dates {
+            format!(" _{}_", self.format_date(commit.timestamp))
+        } else {
+            String::new()
+        }
+    }
+
+    // A commit message cut down to `max_subject_length`, or as it is when it fits
+    fn short_message<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        match self
+            .max_subject_length
+            .and_then(|max| truncate_subject(message, max))
+        {
+            Some(short) => Cow::Owned(short),
+            None => Cow::Borrowed(message),
+        }
+    }
+
+    // A commit message as it appears in a markdown bullet. A shortened message
+    // keeps the full text in the title of a span around it, shown as a tooltip
+    // where the markdown is rendered.
+    fn markdown_message(&self, message: &str) -> String {
+        match self.short_message(message) {
+            Cow::Owned(short) => format!(
+                r#"<span title="{}">{}</span>"#,
+                escape_attribute(message),
+                self.markdown_text(&short)
+            ),
+            Cow::Borrowed(_) => self.markdown_text(message).into_owned(),
+        }
+    }
+
+    // A commit message as it appears in an HTML bullet, escaped, with the full
+    // text as the tooltip when it's shortened
+    fn html_message(&self, message: &str) -> String {
+        match self.short_message(message) {
+            Cow::Owned(short) => format!(
+                r#"<span title="{}">{}</span>"#,
+                escape_attribute(message),
+                escape_html_text(&short)
+            ),
+            Cow::Borrowed(message) => escape_html_text(message),
+        }
+    }
+
+    /// Name a version is displayed with: the tag name, or with `normalize_versions`
+    /// the version pattern's first capture group (`v1.2.3` -> `1.2.3`)
+    pub fn version_label<'a>(&self, name: &'a str) -> &'a str {
+        if !self.normalize_versions {
+            return name;
+        }
+
+        self.version_regex
+            .captures(name)
+            .and_then(|captures| captures.get(1))
+            .map_or(name, |version| version.as_str())
+    }
+
+    // HTML heading level `depth` levels below the title, which is at `heading_base`.
+    // Levels past h6 are clamped to it.
+    fn html_heading_level(&self, depth: u8) -> u8 {
+        (self.heading_base + depth).min(6)
+    }
+
+    // Version name for an HTML heading, linked to its tag page when a URL can be
+    // built for it
+    fn html_version_name(&self, name: &str) -> String {
+        let label = escape_html_text(self.version_label(name));
+        match self.version_tag_url(name) {
+            Some(url) => format!(r#"<a href="{}">{}</a>"#, escape_attribute(&url), label),
+            None => label,
+        }
+    }
+
+    // URL of a version's tag page. The unreleased and initial versions have no tag.
+    fn version_tag_url(&self, name: &str) -> Option<String> {
+        if name == "unreleased" || self.initial_version.as_deref() == Some(name) {
+            return None;
+        }
+        remote::tag_url(&self.tag_url_template, self.remote_url.as_deref(), name)
+    }
+
+    /// Section heading for a commit type, honoring a renamed Other section and the
+    /// locale, and stripping the emoji when they're turned off
+    pub fn heading<'a>(&'a self, commit_type: &CommitType) -> &'a str {
+        let heading = match (commit_type, &self.other_heading) {
+            (CommitType::Other, Some(heading)) => heading,
+            _ => self
+                .headings
+                .get(commit_type)
+                .map_or(commit_type.to_heading(), String::as_str),
         };
 
-        // Get all tag information
-        let tags = self.git.get_tag_info(&self.version_regex)?;
+        if self.emoji {
+            heading
+        } else {
+            strip_emoji(heading)
+        }
+    }
 
-        // Create a map of commit ID -> tag info for quick lookup
-        let mut tag_map: HashMap<String, (String, Option<DateTime<Utc>>)> = HashMap::new();
-        for tag in tags {
-            tag_map.insert(tag.target_commit_id, (tag.name, tag.date));
+    // A section's heading as rendered, followed by its number of bullets with
+    // `section_counts`
+    fn section_heading<'a>(&self, section: &Section<'a>) -> Cow<'a, str> {
+        if self.section_counts {
+            Cow::Owned(format!("{} ({})", section.heading, section.commits.len()))
+        } else {
+            Cow::Borrowed(section.heading)
         }
+    }
 
-        // Process commits
-        let commit_ids = self.git.get_commit_ids()?;
+    // The commit types of a version in rendering order, leaving out Other when it
+    // is hidden
+    fn rendered_commit_types<'a>(&self, version: &'a version::Version) -> Vec<&'a CommitType> {
+        let mut commit_types = ordered_commit_types(version);
+        if self.hide_other {
+            commit_types.retain(|commit_type| **commit_type != CommitType::Other);
+        }
 
-        for commit_id in commit_ids {
-            let commit_info = self.git.get_commit_info(&commit_id)?;
-            let parsed_commit = self.parse_commit(&commit_info);
+        commit_types
+    }
 
-            if tag_map.contains_key(&commit_id) {
-                // Save current version and start a new one
-                if !current_version.commits_by_type.is_empty() {
-                    versions.push(current_version);
-                }
+    // The sections of a version in rendering order. The types of a `type_merges`
+    // rule share one section, placed where the first of them would be, with their
+    // commits listed type by type. A type in several rules goes to the first.
+    fn sections<'a>(&'a self, version: &'a version::Version) -> Vec<Section<'a>> {
+        let mut sections: Vec<Section> = Vec::new();
 
-                let (tag_name, tag_date) = tag_map.get(&commit_id).unwrap().clone();
+        for commit_type in self.rendered_commit_types(version) {
+            let commits = &version.commits_by_type[commit_type];
+            if commits.is_empty() {
+                continue;
+            }
 
-                current_version = version::Version {
-                    name: tag_name,
-                    date: tag_date,
-                    commits_by_type: HashMap::new(),
-                };
+            let merge = self
+                .type_merges
+                .iter()
+                .position(|merge| merge.types.contains(commit_type));
+            if let Some(section) = sections
+                .iter_mut()
+                .find(|section| merge.is_some() && section.merge == merge)
+            {
+                section.types.push(commit_type);
+                section.commits.extend(commits);
+                continue;
             }
 
-            current_version
-                .commits_by_type
-                .entry(parsed_commit.commit_type.clone())
-                .or_default()
-                .push(parsed_commit);
+            let heading = match merge {
+                Some(index) if self.emoji => self.type_merges[index].heading.as_str(),
+                Some(index) => strip_emoji(&self.type_merges[index].heading),
+                None => self.heading(commit_type),
+            };
+            sections.push(Section {
+                merge,
+                types: vec![commit_type],
+                heading,
+                commits: commits.iter().collect(),
+            });
         }
 
-        if !current_version.commits_by_type.is_empty() {
-            versions.push(current_version);
+        sections
+    }
+
+    // A version's commits grouped by scope for the scope-first layout, each scope
+    // with its commits split into the version's sections
+    fn scope_sections<'a>(
+        &'a self,
+        version: &'a version::Version,
+    ) -> Vec<(&'a str, Vec<Section<'a>>)> {
+        let sections = self.sections(version);
+        let commits: Vec<&ParsedCommit> = sections
+            .iter()
+            .flat_map(|section| section.commits.iter().copied())
+            .collect();
+
+        self.scope_groups(version, &commits)
+            .into_iter()
+            .map(|(scope, scope_commits)| (scope, sections_with(&sections, &scope_commits)))
+            .collect()
+    }
+
+    // With `unreleased_date_grouping`, the unreleased version's commits grouped by
+    // the day or week they were committed, newest first, each group split into the
+    // version's sections. None for released versions and without the option.
+    fn unreleased_date_sections<'a>(
+        &'a self,
+        version: &'a version::Version,
+    ) -> Option<Vec<(String, Vec<Section<'a>>)>> {
+        let grouping = self.unreleased_date_grouping?;
+        if version.name != "unreleased" {
+            return None;
         }
 
-        match self.sort_order {
-            SortOrder::Newest => {}
-            SortOrder::Oldest => versions.reverse(),
+        let sections = self.sections(version);
+        let mut groups: BTreeMap<NaiveDate, Vec<&ParsedCommit>> = BTreeMap::new();
+        for section in &sections {
+            for commit in &section.commits {
+                let day = if self.utc {
+                    commit.timestamp.with_timezone(&Utc).date_naive()
+                } else {
+                    commit.timestamp.date_naive()
+                };
+                let start = match grouping {
+                    DateGrouping::Day => day,
+                    DateGrouping::Week => day.week(Weekday::Mon).first_day(),
+                };
+                groups.entry(start).or_default().push(commit);
+            }
         }
 
-        Ok(versions)
+        Some(
+            groups
+                .into_iter()
+                .rev()
+                .map(|(start, commits)| {
+                    let label = match grouping {
+                        DateGrouping::Day => start.format("%Y-%m-%d").to_string(),
+                        DateGrouping::Week => format!("Week of {}", start.format("%Y-%m-%d")),
+                    };
+                    (label, sections_with(&sections, &commits))
+                })
+                .collect(),
+        )
     }
 
-    // Existing methods for writing changelogs remain unchanged
-    pub fn write_markdown_changelog(
-        &self,
-        versions: &[version::Version],
-        path: &Path,
-        title: &str,
-    ) -> std::io::Result<()> {
-        let mut file = File::create(path)?;
+    /// Whether every commit of a version is in a hidden section. Versions that have
+    /// no commits at all, such as unreachable tags, are still rendered.
+    pub fn is_hidden(&self, version: &version::Version) -> bool {
+        !version.commits_by_type.is_empty() && self.rendered_commit_types(version).is_empty()
+    }
 
-        writeln!(&mut file, "# {}\n", title)?;
-        writeln!(
-            &mut file,
-            "All notable changes to this project will be documented in this file.\n"
-        )?;
+    // A type section's commits grouped by scope, labeled and ordered as configured.
+    // Scopes too small in the whole of `version` are listed together last.
+    fn scope_groups<'a>(
+        &'a self,
+        version: &version::Version,
+        commits: &[&'a ParsedCommit],
+    ) -> Vec<(&'a str, Vec<&'a ParsedCommit>)> {
+        let mut groups = group_by_scope(commits, &self.no_scope_label, self.scope_depth);
+        if self.scope_sort == ScopeSort::Count {
+            // Stable, so groups with the same count stay alphabetical
+            groups.sort_by_key(|(_, commits)| std::cmp::Reverse(commits.len()));
+        }
 
-        for version in versions {
-            if version.name == "unreleased" {
-                writeln!(&mut file, "## [unreleased]\n")?;
-            } else if let Some(date) = version.date {
-                writeln!(
-                    &mut file,
-                    "## [{}] - {}\n",
-                    version.name,
-                    date.format("%Y-%m-%d")
-                )?;
-            } else {
-                writeln!(&mut file, "## [{}]\n", version.name)?;
-            }
-
-            let mut commit_types: Vec<&CommitType> = version.commits_by_type.keys().collect();
-            commit_types.sort_by_key(|k| match *k {
-                CommitType::Feature => 0,
-                CommitType::BugFix => 1,
-                CommitType::Documentation => 2,
-                CommitType::Style => 3,
-                CommitType::Refactor => 4,
-                CommitType::Performance => 5,
-                CommitType::Testing => 6,
-                CommitType::Build => 7,
-                CommitType::CI => 8,
-                CommitType::Chore => 9,
-                CommitType::Other => 10,
-            });
+        match self.min_scope_size {
+            Some(min) => fold_scopes(
+                groups,
+                commits,
+                &self.small_scopes(version, min),
+                &self.small_scope_label,
+            ),
+            None => groups,
+        }
+    }
 
-            for commit_type in commit_types {
-                if let Some(commits) = version.commits_by_type.get(commit_type) {
-                    if !commits.is_empty() {
-                        writeln!(&mut file, "### {}\n", commit_type.to_heading())?;
+    // The scopes with fewer than `min` commits among all a version's sections.
+    // Scope-less commits aren't a scope and are never counted.
+    fn small_scopes(&self, version: &version::Version, min: usize) -> HashSet<String> {
+        let commits: Vec<&ParsedCommit> = self
+            .sections(version)
+            .into_iter()
+            .flat_map(|section| section.commits)
+            .collect();
 
-                        for commit in commits {
-                            if let Some(scope) = &commit.scope {
-                                writeln!(&mut file, "- **{}**: {}", scope, commit.message)?;
-                            } else {
-                                writeln!(&mut file, "- {}", commit.message)?;
-                            }
-                        }
+        group_by_scope(&commits, &self.no_scope_label, self.scope_depth)
+            .into_iter()
+            .filter(|(scope, commits)| {
+                // Expanded body items share their commit's id
+                let count = commits
+                    .iter()
+                    .map(|commit| &commit.id)
+                    .collect::<HashSet<_>>()
+                    .len();
+                *scope != self.no_scope_label && count < min
+            })
+            .map(|(scope, _)| scope.to_string())
+            .collect()
+    }
 
-                        writeln!(&mut file)?;
-                    }
+    // A version's commits grouped by author, most commits first and then by name.
+    // Within an author, commits keep the order of their type sections.
+    fn author_groups<'a>(
+        &self,
+        version: &'a version::Version,
+    ) -> Vec<(&'a str, Vec<&'a ParsedCommit>)> {
+        let mut groups: Vec<(&str, Vec<&ParsedCommit>)> = Vec::new();
+        for commit_type in self.rendered_commit_types(version) {
+            for commit in &version.commits_by_type[commit_type] {
+                let author = if commit.author.is_empty() {
+                    UNKNOWN_AUTHOR
+                } else {
+                    commit.author.as_str()
+                };
+                match groups.iter_mut().find(|(name, _)| *name == author) {
+                    Some((_, commits)) => commits.push(commit),
+                    None => groups.push((author, vec![commit])),
                 }
             }
         }
 
-        writeln!(&mut file, "<!-- generated by chronicle -->")?;
+        groups.sort_by_key(|(author, _)| *author);
+        // Stable, so authors with the same count stay alphabetical
+        groups.sort_by_key(|(_, commits)| std::cmp::Reverse(commits.len()));
+        groups
+    }
 
-        Ok(())
+    // Issues closed by the shown commits of a version, without duplicates and in
+    // numeric order. Empty unless closed issues are listed.
+    fn closed_issues(&self, version: &version::Version) -> Vec<u64> {
+        if !self.closed_issues {
+            return Vec::new();
+        }
+
+        let issues: BTreeSet<u64> = self
+            .rendered_commit_types(version)
+            .into_iter()
+            .flat_map(|commit_type| &version.commits_by_type[commit_type])
+            .flat_map(|commit| commit.closed_issues.iter().copied())
+            .collect();
+        issues.into_iter().collect()
     }
 
-    pub fn write_html_changelog(
-        &self,
-        versions: &[version::Version],
-        path: &Path,
-        title: &str,
-    ) -> std::io::Result<()> {
-        // HTML generation code (unchanged)
-        let mut file = File::create(path)?;
+    // Heading of the closed issues section, without its emoji when they're off
+    fn closed_issues_heading(&self) -> &'static str {
+        if self.emoji {
+            CLOSED_ISSUES_HEADING
+        } else {
+            strip_emoji(CLOSED_ISSUES_HEADING)
+        }
+    }
 
-        // Write HTML header
-        write!(
-            &mut file,
-            r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <title>{}</title>
-    <style>
-        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; line-height: 1.5; max-width: 800px; margin: 0 auto; padding: 20px; color: #24292e; }}
-        h1 {{ border-bottom: 1px solid #eaecef; padding-bottom: 0.3em; }}
-        h2 {{ margin-top: 24px; margin-bottom: 16px; font-weight: 600; line-height: 1.25; border-bottom: 1px solid #eaecef; padding-bottom: 0.3em; }}
-        h3 {{ margin-top: 24px; margin-bottom: 16px; font-weight: 600; line-height: 1.25; }}
-        ul {{ padding-left: 2em; }}
-        li {{ margin: 0.25em 0; }}
-        .footer {{ margin-top: 30px; color: #6a737d; font-size: 0.9em; text-align: center; }}
-    </style>
-</head>
-<body>
-    <h1>{}</h1>
-    <p>All notable changes to this project will be documented in this file.</p>
-"#,
-            title, title
-        )?;
+    // Link to an issue on the remote, if there is one
+    fn issue_url(&self, number: u64) -> Option<String> {
+        remote::issue_url(self.remote_url.as_deref(), number)
+    }
 
-        for version in versions {
-            if version.name == "unreleased" {
-                writeln!(&mut file, "    <h2>[unreleased]</h2>")?;
-            } else if let Some(date) = version.date {
-                writeln!(
-                    &mut file,
-                    "    <h2>[{}] - {}</h2>",
-                    version.name,
-                    date.format("%Y-%m-%d")
-                )?;
+    // Link to a commit's pull request with `link_prs`, if the commit has one and
+    // there is a remote
+    fn pull_request_url(&self, commit: &ParsedCommit) -> Option<(u64, Option<String>)> {
+        let number = commit.pr?;
+        let url = if self.link_prs {
+            remote::pull_request_url(self.remote_url.as_deref(), number)
+        } else {
+            None
+        };
+
+        Some((number, url))
+    }
+
+    // The top-level paths a commit changed, as many as `max_paths` allows, e.g.
+    // "docs/, src/ and 2 more"; None when there are none to show
+    fn paths_text(&self, commit: &ParsedCommit) -> Option<String> {
+        if commit.paths.is_empty() {
+            return None;
+        }
+
+        let shown = commit.paths.len().min(self.max_paths);
+        let mut text = commit.paths[..shown].join(", ");
+        if shown < commit.paths.len() {
+            text.push_str(&format!(" and {} more", commit.paths.len() - shown));
+        }
+        Some(text)
+    }
+
+    // Link to an author's profile, when there is an author URL template and a
+    // login for the name
+    fn author_url(&self, author: &str) -> Option<String> {
+        let template = self.author_url_template.as_deref()?;
+        let login = self.author_logins.get(author)?;
+        remote::author_url(template, self.remote_url.as_deref(), login)
+    }
+
+    // Link to a commit on the remote, from the commit URL template
+    fn commit_url(&self, commit: &ParsedCommit) -> Option<String> {
+        remote::commit_url(
+            &self.commit_url_template,
+            self.remote_url.as_deref(),
+            &commit.id,
+        )
+    }
+
+    // Pull request suffix of a markdown bullet: " (#12)", linked with `link_prs`
+    fn markdown_pull_request(&self, commit: &ParsedCommit) -> String {
+        match self.pull_request_url(commit) {
+            Some((number, Some(url))) => format!(" ([#{}]({}))", number, url),
+            Some((number, None)) => format!(" (#{})", number),
+            None => String::new(),
+        }
+    }
+
+    // Pull request suffix of an HTML list item
+    fn html_pull_request(&self, commit: &ParsedCommit) -> String {
+        match self.pull_request_url(commit) {
+            Some((number, Some(url))) => format!(r#" (<a href="{}">#{}</a>)"#, url, number),
+            Some((number, None)) => format!(" (#{})", number),
+            None => String::new(),
+        }
+    }
+
+    // Pull request suffix of a Slack bullet
+    fn slack_pull_request(&self, commit: &ParsedCommit) -> String {
+        match self.pull_request_url(commit) {
+            Some((number, Some(url))) => format!(" (<{}|#{}>)", url, number),
+            Some((number, None)) => format!(" (#{})", number),
+            None => String::new(),
+        }
+    }
+
+    // Pull request suffix of an AsciiDoc bullet, linked with a URL macro
+    fn asciidoc_pull_request(&self, commit: &ParsedCommit) -> String {
+        match self.pull_request_url(commit) {
+            Some((number, Some(url))) => format!(" ({}[#{}])", url, number),
+            Some((number, None)) => format!(" (#{})", number),
+            None => String::new(),
+        }
+    }
+
+    // Pull request suffix of a reStructuredText bullet, linked with an anonymous
+    // hyperlink
+    fn rst_pull_request(&self, commit: &ParsedCommit) -> String {
+        match self.pull_request_url(commit) {
+            Some((number, Some(url))) => format!(" (`#{} <{}>`__)", number, url),
+            Some((number, None)) => format!(" (#{})", number),
+            None => String::new(),
+        }
+    }
+
+    pub fn parse_commit(&self, commit_info: &CommitInfo) -> ParsedCommit {
+        let commit = parser::parse_commit(commit_info, &self.commit_regex);
+        self.empty_as_other(commit, parser::subject(&commit_info.message))
+    }
+
+    // Whether a parsed commit passes the configured filters and should be bucketed
+    fn is_included(&self, commit: &ParsedCommit) -> bool {
+        if !self.scopes.is_empty() {
+            let matches = |scope: &str| self.scopes.iter().any(|s| s.eq_ignore_ascii_case(scope));
+            let included = if commit.scopes.is_empty() {
+                matches(NO_SCOPE)
             } else {
-                writeln!(&mut file, "    <h2>[{}]</h2>", version.name)?;
-            }
-
-            let mut commit_types: Vec<&CommitType> = version.commits_by_type.keys().collect();
-            commit_types.sort_by_key(|k| match *k {
-                CommitType::Feature => 0,
-                CommitType::BugFix => 1,
-                CommitType::Documentation => 2,
-                CommitType::Style => 3,
-                CommitType::Refactor => 4,
-                CommitType::Performance => 5,
-                CommitType::Testing => 6,
-                CommitType::Build => 7,
-                CommitType::CI => 8,
-                CommitType::Chore => 9,
-                CommitType::Other => 10,
-            });
+                commit.scopes.iter().any(|scope| matches(scope))
+            };
+            if !included {
+                return false;
+            }
+        }
 
-            for commit_type in commit_types {
-                if let Some(commits) = version.commits_by_type.get(commit_type) {
-                    if !commits.is_empty() {
-                        writeln!(&mut file, "    <h3>{}</h3>", commit_type.to_heading())?;
-                        writeln!(&mut file, "    <ul>")?;
+        if !self.include_types.is_empty() && !self.include_types.contains(&commit.commit_type) {
+            return false;
+        }
 
-                        for commit in commits {
-                            if let Some(scope) = &commit.scope {
-                                writeln!(
-                                    &mut file,
-                                    "        <li><strong>{}</strong>: {}</li>",
-                                    scope, commit.message
-                                )?;
-                            } else {
-                                writeln!(&mut file, "        <li>{}</li>", commit.message)?;
-                            }
-                        }
+        !self.exclude_types.contains(&commit.commit_type)
+    }
+
+    // Parses a commit and keeps its subject around for the ignore patterns
+    fn parse_entry(&self, commit_info: &CommitInfo) -> CachedCommit {
+        CachedCommit {
+            subject: parser::subject(&commit_info.message).to_string(),
+            commit: self.parse_commit(commit_info),
+        }
+    }
+
+    // With `empty_as_other`, a commit whose pattern matched with an empty message
+    // (e.g. a bare `feat:`) moves to Other with its whole subject as the message.
+    // Part of parsing, so the commit type default applies to it afterwards.
+    fn empty_as_other(&self, commit: ParsedCommit, subject: &str) -> ParsedCommit {
+        if self.empty_as_other {
+            parser::empty_as_other(commit, subject)
+        } else {
+            commit
+        }
+    }
+
+    // Moves a commit of no known type to the `commit_type_default` type, or drops
+    // it. Applied after the cache, which holds commits as the pattern parsed them.
+    fn with_type_default(&self, mut commit: ParsedCommit) -> Option<ParsedCommit> {
+        if commit.commit_type != CommitType::Other {
+            return Some(commit);
+        }
+
+        match &self.commit_type_default {
+            CommitTypeDefault::Type(commit_type) => {
+                commit.commit_type = commit_type.clone();
+                Some(commit)
+            }
+            CommitTypeDefault::Drop => None,
+        }
+    }
+
+    // Whether a commit subject matches one of the ignore patterns
+    fn is_ignored(&self, subject: &str) -> bool {
+        self.ignore_regexes
+            .iter()
+            .any(|regex| regex.is_match(subject))
+    }
+
+    // Tags matching the version pattern, without rolling tags, or prereleases
+    // when they're excluded. Their commits then belong to the next version (or
+    // stay unreleased). With the committer as the date source, each is dated by
+    // its commit.
+    fn version_tags(&self) -> Result<Vec<TagInfo>> {
+        let mut tags = self.git.get_tag_info(&self.version_regex)?;
+        tags.retain(|tag| !self.rolling_tags.contains(&tag.name));
+        if self.prerelease == Prerelease::Exclude {
+            tags.retain(|tag| !version::is_prerelease(&tag.name));
+        }
+        if self.version_date_source == VersionDateSource::Committer {
+            for tag in &mut tags {
+                // A tag of something other than a commit keeps its own date
+                if let Ok(commit_info) = self.git.get_commit_info(&tag.target_commit_id) {
+                    tag.date = Some(commit_info.timestamp);
+                }
+            }
+        }
+
+        Ok(tags)
+    }
+
+    // Whether a version is left out by the prerelease filter: with `only`, every
+    // stable version is. The unreleased section never is.
+    fn is_filtered_release(&self, name: &str) -> bool {
+        self.prerelease == Prerelease::Only && name != "unreleased" && !version::is_prerelease(name)
+    }
+
+    // Map of commit ID -> (tag name, tag date) for every version tag. When several
+    // tags point at the same commit, such as v1.2.0 and v1.2.0-final, the shortest
+    // name is used (the alphabetically first among equally long ones), whatever
+    // order the provider lists them in.
+    fn tag_map(&self) -> Result<HashMap<String, VersionTag>> {
+        let tags = self.version_tags()?;
+        let mut tag_map: HashMap<String, VersionTag> = HashMap::new();
+
+        for tag in tags {
+            let kept = tag_map.get(&tag.target_commit_id);
+            if kept.is_none_or(|(name, _)| (name.len(), name) > (tag.name.len(), &tag.name)) {
+                tag_map.insert(tag.target_commit_id, (tag.name, tag.date));
+            }
+        }
+
+        Ok(tag_map)
+    }
 
-                        writeln!(&mut file, "    </ul>")?;
+    // The commit the walk stops at, if bounded by `since`. The last tag is the
+    // first version tag met walking back from HEAD, and a base stops the walk at
+    // its merge base with HEAD.
+    fn since_commit(&self) -> Result<Option<String>> {
+        match &self.since {
+            None => Ok(None),
+            Some(Since::Tag(name)) => {
+                let name_regex = Regex::new(&format!("^{}$", regex::escape(name)))?;
+                match self.git.get_tag_info(&name_regex)?.into_iter().next() {
+                    Some(tag) => Ok(Some(tag.target_commit_id)),
+                    None => Err(GitError::TagNotFound(name.clone()).into()),
+                }
+            }
+            Some(Since::LastTag) => {
+                let tag_map = self.tag_map()?;
+                for commit_info in self.git.commits()? {
+                    let commit_info = commit_info?;
+                    if tag_map.contains_key(&commit_info.id) {
+                        return Ok(Some(commit_info.id));
                     }
                 }
+                Err(GitError::NoVersionTag.into())
             }
+            Some(Since::Base(reference)) => Ok(Some(self.git.merge_base(reference)?)),
         }
+    }
 
-        write!(
-            &mut file,
-            r#"    <div class="footer">Generated by chronicle</div>
-</body>
-</html>
-"#
-        )?;
+    // Every commit in walk order, parsed, stopping short of the `since` boundary. With a cache, only the ids are walked and
+    // commits missing from the cache are read from the repository. Without one and
+    // with several jobs, the commits within the limit are read up front and parsed
+    // on that many threads.
+    fn parsed_commits(&self) -> Result<Box<dyn Iterator<Item = Result<CachedCommit>> + '_>> {
+        let boundary = self.since_commit()?;
+        let Some(cache) = &self.cache else {
+            let commit_infos = match &boundary {
+                Some(boundary) => self.git.commits_after(boundary)?,
+                None => self.git.commits()?,
+            };
+            if self.jobs > 1 {
+                let commit_infos = commit_infos
+                    .take(self.limit.unwrap_or(usize::MAX))
+                    .collect::<std::result::Result<Vec<_>, GitError>>()?;
+                let commits = parser::parse_commits(&commit_infos, &self.commit_regex, self.jobs);
+                let entries: Vec<CachedCommit> = commit_infos
+                    .iter()
+                    .zip(commits)
+                    .map(|(commit_info, commit)| {
+                        let subject = parser::subject(&commit_info.message);
+                        CachedCommit {
+                            subject: subject.to_string(),
+                            commit: self.empty_as_other(commit, subject),
+                        }
+                    })
+                    .collect();
+                return Ok(Box::new(entries.into_iter().map(Ok)));
+            }
 
-        Ok(())
+            return Ok(Box::new(
+                commit_infos.map(|commit_info| Ok(self.parse_entry(&commit_info?))),
+            ));
+        };
+        let commit_ids = match &boundary {
+            Some(boundary) => self.git.get_commit_ids_after(boundary)?,
+            None => self.git.get_commit_ids()?,
+        };
+
+        Ok(Box::new(commit_ids.into_iter().map(move |id| {
+            if let Some(entry) = cache.get(&id) {
+                return Ok(entry);
+            }
+
+            let entry = self.parse_entry(&self.git.get_commit_info(&id)?);
+            cache.insert(entry.clone());
+            Ok(entry)
+        })))
+    }
+
+    /// Streams the commits that pass the configured filters in walk order (newest
+    /// first), each paired with the version it belongs to. Commits are read from
+    /// the provider and parsed one at a time as the iterator advances.
+    pub fn iter_commits(&self) -> Result<impl Iterator<Item = Result<VersionedCommit>> + '_> {
+        let mut tag_map = self.tag_map()?;
+        // Tags on other branches are never passed, so the initial version starts
+        // after the last of those reachable from HEAD
+        let mut tags_ahead = match &self.initial_version {
+            Some(_) => {
+                let commit_ids: HashSet<String> = self.git.get_commit_ids()?.into_iter().collect();
+                tag_map.keys().filter(|id| commit_ids.contains(*id)).count()
+            }
+            None => 0,
+        };
+        // The limit cuts the walk before versions are assigned, so the oldest
+        // version in the window keeps whatever of its commits fit
+        let commits = self
+            .parsed_commits()?
+            .take(self.limit.unwrap_or(usize::MAX));
+        let mut version: VersionTag = ("unreleased".to_string(), None);
+
+        Ok(commits.filter_map(move |entry| {
+            let CachedCommit { subject, commit } = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            // A tagged commit starts a new version that lasts until the next tag
+            if let Some(tag) = tag_map.remove(&commit.id) {
+                version = tag;
+                tags_ahead = tags_ahead.saturating_sub(1);
+            } else if tags_ahead == 0 && version.0 != "unreleased" {
+                // Past the earliest tag, the rest is the initial version, dated
+                // by its newest commit
+                if let Some(name) = self
+                    .initial_version
+                    .as_ref()
+                    .filter(|name| **name != version.0)
+                {
+                    version = (name.clone(), Some(commit.timestamp));
+                }
+            }
+
+            let commit = self.with_type_default(commit)?;
+
+            if self.is_ignored(&subject)
+                || !self.is_included(&commit)
+                || self.is_filtered_release(&version.0)
+            {
+                return None;
+            }
+
+            Some(Ok(VersionedCommit {
+                version: version.0.clone(),
+                version_date: version.1,
+                commit,
+            }))
+        }))
+    }
+
+    /// Streams the versions in walk order (newest first), each yielded as soon as
+    /// the walk crosses into the next one, so a caller that only needs the first
+    /// few doesn't read the rest of the history. The sort order isn't applied and
+    /// versions for unreachable tags aren't added, since both need every version
+    /// at hand; `generate_changelog` collects from here and does that.
+    pub fn iter_versions(&self) -> Result<impl Iterator<Item = Result<version::Version>> + '_> {
+        Ok(self.unsorted_versions()?.map(|version| {
+            version.map(|mut version| {
+                self.finish_version(&mut version);
+                version
+            })
+        }))
+    }
+
+    // The versions of `iter_versions` before their commits are sorted and their
+    // scopes collapsed
+    fn unsorted_versions(&self) -> Result<impl Iterator<Item = Result<version::Version>> + '_> {
+        let mut commits = self.iter_commits()?;
+        let mut current: Option<version::Version> = None;
+
+        let versions = std::iter::from_fn(move || {
+            loop {
+                let VersionedCommit {
+                    version,
+                    version_date,
+                    commit,
+                } = match commits.next() {
+                    Some(Ok(versioned)) => versioned,
+                    Some(Err(err)) => return Some(Err(err)),
+                    None => return current.take().map(Ok),
+                };
+
+                // A commit of another version completes the current one
+                let completed = current.take_if(|current| current.name != version);
+                let current = current.get_or_insert_with(|| version::Version {
+                    name: version,
+                    date: version_date,
+                    commits_by_type: HashMap::new(),
+                    diff_stat: None,
+                });
+                if let Err(err) = self.add_commit(current, commit) {
+                    return Some(Err(err));
+                }
+
+                if let Some(completed) = completed {
+                    return Some(Ok(completed));
+                }
+            }
+        });
+
+        Ok(versions.filter(|version| {
+            version
+                .as_ref()
+                .map_or(true, |version| self.meets_unreleased_requires(version))
+        }))
+    }
+
+    // Orders a version's commits and collapses its scopes for rendering
+    fn finish_version(&self, version: &mut version::Version) {
+        self.sort_commits(version);
+        self.collapse_scopes(version);
+    }
+
+    // Buckets fill in walk order, which differs between providers and for commits
+    // made in the same second, so they are sorted by timestamp and then by id. The
+    // sort is stable, keeping the items of an expanded body in order.
+    fn sort_commits(&self, version: &mut version::Version) {
+        for commits in version.commits_by_type.values_mut() {
+            commits.sort_by(|a, b| {
+                let by_time = if self.reverse_within_version {
+                    a.timestamp.cmp(&b.timestamp)
+                } else {
+                    b.timestamp.cmp(&a.timestamp)
+                };
+                by_time.then_with(|| a.id.cmp(&b.id))
+            });
+        }
+    }
+
+    // Replaces the commits of each collapsed scope in a bucket with one summary
+    // commit where the newest of them was, keeping their closed issues and
+    // changed paths. A scope with a single commit in the bucket keeps it as it is.
+    fn collapse_scopes(&self, version: &mut version::Version) {
+        for collapse in &self.scope_collapses {
+            let in_scope = |commit: &ParsedCommit| {
+                commit
+                    .scopes
+                    .iter()
+                    .any(|scope| scope.eq_ignore_ascii_case(&collapse.scope))
+            };
+
+            for commits in version.commits_by_type.values_mut() {
+                // Expanded body items share their commit's id
+                let collapsed: Vec<&ParsedCommit> =
+                    commits.iter().filter(|c| in_scope(c)).collect();
+                let count = collapsed
+                    .iter()
+                    .map(|commit| &commit.id)
+                    .collect::<HashSet<_>>()
+                    .len();
+                if count < 2 {
+                    continue;
+                }
+
+                let mut summary = collapsed[0].clone();
+                summary.scopes = vec![collapse.scope.clone()];
+                summary.raw_scope = Some(collapse.scope.clone());
+                summary.message = collapse.summary.replace("{count}", &count.to_string());
+                summary.signed = collapsed.iter().all(|commit| commit.signed);
+                summary.breaking = collapsed.iter().any(|commit| commit.breaking);
+                summary.pr = None;
+                summary.body_items.clear();
+                summary.closed_issues = collapsed
+                    .iter()
+                    .flat_map(|commit| commit.closed_issues.iter().copied())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                summary.paths = collapsed
+                    .iter()
+                    .flat_map(|commit| commit.paths.iter().cloned())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+
+                let first = commits.iter().position(in_scope).unwrap_or(0);
+                commits.retain(|commit| !in_scope(commit));
+                commits.insert(first, summary);
+            }
+        }
+    }
+
+    // Adds a commit to its version's type bucket, along with its diff stat and
+    // changed paths when those are shown
+    fn add_commit(&self, version: &mut version::Version, mut commit: ParsedCommit) -> Result<()> {
+        if let Some(diff_stat) = self.read_changes(&mut commit)? {
+            version.diff_stat.get_or_insert_default().add(diff_stat);
+        }
+        self.bucket_commit(version, commit);
+        Ok(())
+    }
+
+    // Fills in the paths a commit changed when those are shown, and returns its
+    // diff stat when that is shown
+    fn read_changes(&self, commit: &mut ParsedCommit) -> Result<Option<DiffStat>> {
+        if self.show_paths {
+            commit.paths = top_level_paths(self.git.changed_paths(&commit.id)?);
+        }
+        if self.diffstat {
+            return Ok(Some(self.git.diff_stat(&commit.id)?));
+        }
+        Ok(None)
+    }
+
+    // Adds a commit to its version's type bucket. With `expand_body_bullets`, a
+    // commit with a bulleted body is added once per item.
+    fn bucket_commit(&self, version: &mut version::Version, commit: ParsedCommit) {
+        let bucket = version
+            .commits_by_type
+            .entry(commit.commit_type.clone())
+            .or_default();
+        if self.expand_body_bullets && !commit.body_items.is_empty() {
+            // Each item becomes a bullet of its own, keeping the commit's type,
+            // scope and links
+            for item in &commit.body_items {
+                bucket.push(ParsedCommit {
+                    message: item.clone(),
+                    ..commit.clone()
+                });
+            }
+        } else {
+            bucket.push(commit);
+        }
+    }
+
+    // Without a commit of a required type, the unreleased section is dropped
+    fn meets_unreleased_requires(&self, version: &version::Version) -> bool {
+        self.unreleased_requires.is_empty()
+            || version.name != "unreleased"
+            || self
+                .unreleased_requires
+                .iter()
+                .any(|commit_type| version.commits_by_type.contains_key(commit_type))
+    }
+
+    pub fn generate_changelog(&self) -> Result<Vec<version::Version>> {
+        let mut versions = if self.group_prereleases_under_stable {
+            // Merged before sorting, so the stable version's commits are ordered
+            // and collapsed together
+            let versions = self.unsorted_versions()?.collect::<Result<Vec<_>>>()?;
+            let mut versions = group_prereleases(versions);
+            for version in &mut versions {
+                self.finish_version(version);
+            }
+            versions
+        } else {
+            self.iter_versions()?.collect::<Result<Vec<_>>>()?
+        };
+
+        if self.include_unreachable_tags {
+            let mut unreachable = self.unreachable_tags()?;
+            unreachable.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.name.cmp(&b.name)));
+
+            for tag in unreachable {
+                if self.is_filtered_release(&tag.name) {
+                    continue;
+                }
+                insert_by_date(
+                    &mut versions,
+                    version::Version {
+                        name: tag.name,
+                        date: tag.date,
+                        commits_by_type: HashMap::new(),
+                        diff_stat: None,
+                    },
+                );
+            }
+        }
+
+        match self.sort_order {
+            SortOrder::Newest => {}
+            SortOrder::Oldest => versions.reverse(),
+        }
+
+        Ok(versions)
+    }
+
+    /// Versions read back from earlier output, newest first, ordered for rendering
+    /// like those of `generate_changelog`. Their commits were parsed and filtered
+    /// when the output was written, so only rendering options apply to them.
+    /// Changed paths and diff stats are kept when shown, as far as the output
+    /// recorded them.
+    pub fn imported_changelog(&self, mut versions: Vec<version::Version>) -> Vec<version::Version> {
+        for version in &mut versions {
+            if !self.diffstat {
+                version.diff_stat = None;
+            }
+            // Bucketed again, so body items are expanded as for the repository
+            let commits: Vec<ParsedCommit> = std::mem::take(&mut version.commits_by_type)
+                .into_values()
+                .flatten()
+                .collect();
+            for mut commit in commits {
+                if !self.show_paths {
+                    commit.paths.clear();
+                }
+                self.bucket_commit(version, commit);
+            }
+            self.finish_version(version);
+        }
+
+        match self.sort_order {
+            SortOrder::Newest => {}
+            SortOrder::Oldest => versions.reverse(),
+        }
+
+        versions
+    }
+
+    /// Write the parsed commit cache back to disk, if caching is enabled. Entries
+    /// for commits that are no longer in the history are dropped.
+    pub fn save_cache(&self) -> Result<()> {
+        if let Some(cache) = &self.cache {
+            let commit_ids: HashSet<String> = self.git.get_commit_ids()?.into_iter().collect();
+            cache.save(&commit_ids)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tags matching the version pattern whose target commit is never reached by
+    /// the commit walk, e.g. tags on another branch. Sorted by name.
+    pub fn unreachable_tags(&self) -> Result<Vec<TagInfo>> {
+        let commit_ids: HashSet<String> = self.git.get_commit_ids()?.into_iter().collect();
+
+        let mut tags: Vec<TagInfo> = self
+            .version_tags()?
+            .into_iter()
+            .filter(|tag| !commit_ids.contains(&tag.target_commit_id))
+            .collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(tags)
+    }
+
+    /// Writes the changelog in `format`. NDJSON ignores `versions` and `title` and
+    /// streams the commits from the repository instead. Document templates only
+    /// apply to markdown, HTML, Slack, AsciiDoc and reStructuredText.
+    pub fn write_changelog(
+        &self,
+        versions: &[version::Version],
+        format: OutputFormat,
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        // NDJSON is read line by line by other programs, so it stays as it is
+        if format == OutputFormat::NdJson {
+            return self.write_ndjson_changelog(out);
+        }
+
+        let mut out = LineEndingWriter::new(out, self.line_ending, self.bom)?;
+        self.write_format(versions, format, &mut out, title)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    // Writes the changelog in `format`, with a document template when one applies
+    fn write_format(
+        &self,
+        versions: &[version::Version],
+        format: OutputFormat,
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if let Some(template) = &self.document_template
+            && !matches!(format, OutputFormat::NdJson | OutputFormat::Yaml)
+        {
+            let rendered = template
+                .render(&self.template_context(versions, title))
+                .map_err(ChronicleError::InvalidTemplate)?;
+            out.write_all(rendered.as_bytes())?;
+            return Ok(());
+        }
+
+        match format {
+            OutputFormat::Markdown => self.write_markdown_changelog(versions, out, title),
+            OutputFormat::Html => self.write_html_changelog(versions, out, title),
+            OutputFormat::NdJson => self.write_ndjson_changelog(out),
+            OutputFormat::Slack => self.write_slack_changelog(versions, out, title),
+            OutputFormat::Yaml => self.write_yaml_changelog(versions, out, title),
+            OutputFormat::Asciidoc => self.write_asciidoc_changelog(versions, out, title),
+            OutputFormat::Rst => self.write_rst_changelog(versions, out, title),
+        }
+    }
+
+    /// Values available to a `--template` document: the title, footer and remote
+    /// URL, and the shown versions with their type sections and commits
+    pub fn template_context(&self, versions: &[version::Version], title: &str) -> Context {
+        let text = |value: &str| Value::Text(value.to_string());
+
+        let versions = versions
+            .iter()
+            .filter(|version| !self.is_hidden(version))
+            .map(|version| {
+                let mut sections = Vec::new();
+                let mut commits = Vec::new();
+                for section in self.sections(version) {
+                    let section_commits: Vec<Context> = section
+                        .commits
+                        .iter()
+                        .map(|commit| self.commit_context(commit))
+                        .collect();
+                    let types: Vec<&str> = section.types.iter().map(|t| t.as_str()).collect();
+                    commits.extend(section_commits.iter().cloned());
+                    sections.push(vec![
+                        ("type", text(&types.join(","))),
+                        ("heading", text(section.heading)),
+                        ("commits", Value::List(section_commits)),
+                    ]);
+                }
+                let tag_url = self.version_tag_url(&version.name);
+                let closed_issues = self
+                    .closed_issues(version)
+                    .into_iter()
+                    .map(|issue| {
+                        vec![
+                            ("number", Value::Text(issue.to_string())),
+                            (
+                                "url",
+                                Value::Text(self.issue_url(issue).unwrap_or_default()),
+                            ),
+                        ]
+                    })
+                    .collect();
+
+                vec![
+                    ("name", text(&version.name)),
+                    ("label", text(self.version_label(&version.name))),
+                    ("unreleased", Value::Bool(version.name == "unreleased")),
+                    (
+                        "date",
+                        Value::Text(
+                            version
+                                .date
+                                .map_or(String::new(), |date| self.format_date(date)),
+                        ),
+                    ),
+                    ("anchor", Value::Text(version_anchor(&version.name))),
+                    ("tag_url", Value::Text(tag_url.unwrap_or_default())),
+                    ("commit_count", Value::Text(commits.len().to_string())),
+                    (
+                        "diffstat",
+                        Value::Text(
+                            version
+                                .diff_stat
+                                .map(|diff_stat| diff_stat.to_string())
+                                .unwrap_or_default(),
+                        ),
+                    ),
+                    ("sections", Value::List(sections)),
+                    ("commits", Value::List(commits)),
+                    ("closed_issues", Value::List(closed_issues)),
+                ]
+            })
+            .collect();
+
+        vec![
+            ("title", text(title)),
+            (
+                "footer",
+                Value::Text(self.footer_text().unwrap_or_default()),
+            ),
+            (
+                "remote_url",
+                Value::Text(self.remote_url.clone().unwrap_or_default()),
+            ),
+            ("versions", Value::List(versions)),
+        ]
+    }
+
+    // A commit's values in a `--template` document
+    fn commit_context(&self, commit: &ParsedCommit) -> Context {
+        vec![
+            ("hash", Value::Text(commit.id.clone())),
+            (
+                "url",
+                Value::Text(self.commit_url(commit).unwrap_or_default()),
+            ),
+            (
+                "short_hash",
+                Value::Text(commit.id.chars().take(SHORT_HASH_LENGTH).collect()),
+            ),
+            ("type", Value::Text(commit.commit_type.as_str().to_string())),
+            ("scope", Value::Text(commit.scope().unwrap_or_default())),
+            ("message", Value::Text(commit.message.clone())),
+            ("date", Value::Text(self.format_date(commit.timestamp))),
+            ("author", Value::Text(commit.author.clone())),
+            (
+                "author_url",
+                Value::Text(self.author_url(&commit.author).unwrap_or_default()),
+            ),
+            (
+                "pr",
+                Value::Text(commit.pr.map(|pr| pr.to_string()).unwrap_or_default()),
+            ),
+            (
+                "pr_url",
+                Value::Text(
+                    self.pull_request_url(commit)
+                        .and_then(|(_, url)| url)
+                        .unwrap_or_default(),
+                ),
+            ),
+            (
+                "paths",
+                Value::Text(self.paths_text(commit).unwrap_or_default()),
+            ),
+            ("breaking", Value::Bool(commit.breaking)),
+            ("signed", Value::Bool(commit.signed)),
+        ]
+    }
+
+    pub fn write_markdown_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if !self.notes_only && !self.fragment {
+            writeln!(out, "# {}\n", title)?;
+            writeln!(
+                out,
+                "All notable changes to this project will be documented in this file.\n"
+            )?;
+        }
+
+        if self.breaking_summary {
+            self.write_breaking_summary(versions, out)?;
+        }
+
+        for version in versions.iter().filter(|version| !self.is_hidden(version)) {
+            if self.breaking_summary {
+                writeln!(out, r#"<a id="{}"></a>"#, version_anchor(&version.name))?;
+                writeln!(out)?;
+            }
+
+            if version.name == "unreleased" {
+                writeln!(out, "## [unreleased]\n")?;
+            } else if let Some(date) = version.date {
+                writeln!(
+                    out,
+                    "## [{}] - {}\n",
+                    self.version_label(&version.name),
+                    self.format_date(date)
+                )?;
+            } else {
+                writeln!(out, "## [{}]\n", self.version_label(&version.name))?;
+            }
+
+            if let Some(summary) = self.summaries.get(&version.name) {
+                writeln!(out, "_{}_\n", self.markdown_text(summary))?;
+            }
+            if let Some(diff_stat) = version.diff_stat {
+                writeln!(out, "_{}_\n", diff_stat)?;
+            }
+
+            if self.group_by == GroupBy::Author {
+                for (author, commits) in self.author_groups(version) {
+                    match self.author_url(author) {
+                        Some(url) => {
+                            writeln!(out, "### [{}]({})\n", self.markdown_text(author), url)?
+                        }
+                        None => writeln!(out, "### {}\n", self.markdown_text(author))?,
+                    }
+                    for commit in commits {
+                        self.write_markdown_bullet(commit, true, out)?;
+                    }
+                    writeln!(out)?;
+                }
+                self.write_markdown_closed_issues(version, out)?;
+                continue;
+            }
+
+            if let Some(date_sections) = self.unreleased_date_sections(version) {
+                for (date, sections) in date_sections {
+                    writeln!(out, "### {}\n", date)?;
+                    for section in sections {
+                        writeln!(out, "#### {}\n", self.section_heading(&section))?;
+                        for commit in section.commits {
+                            self.write_markdown_bullet(commit, true, out)?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+                self.write_markdown_closed_issues(version, out)?;
+                continue;
+            }
+
+            if self.layout == Layout::ScopeFirst {
+                for (scope, sections) in self.scope_sections(version) {
+                    writeln!(out, "### {}\n", self.markdown_text(scope))?;
+                    for section in sections {
+                        writeln!(out, "#### {}\n", self.section_heading(&section))?;
+                        for commit in section.commits {
+                            self.write_markdown_bullet(commit, false, out)?;
+                        }
+                        writeln!(out)?;
+                    }
+                }
+                self.write_markdown_closed_issues(version, out)?;
+                continue;
+            }
+
+            for section in self.sections(version) {
+                let commits = &section.commits;
+                writeln!(out, "### {}\n", self.section_heading(&section))?;
+
+                if self.group_by_scope {
+                    for (scope, commits) in self.scope_groups(version, commits) {
+                        writeln!(out, "#### {}\n", self.markdown_text(scope))?;
+
+                        for commit in commits {
+                            self.write_markdown_bullet(commit, false, out)?;
+                        }
+
+                        writeln!(out)?;
+                    }
+                } else {
+                    for commit in commits {
+                        self.write_markdown_bullet(commit, true, out)?;
+                    }
+
+                    writeln!(out)?;
+                }
+            }
+
+            self.write_markdown_closed_issues(version, out)?;
+        }
+
+        if let Some(footer) = self.footer_text().filter(|_| !self.fragment) {
+            writeln!(out, "<!-- {} {} -->", self.footer_marker, footer)?;
+        }
+
+        Ok(())
+    }
+
+    // The closed issues section of a version, linking each issue when there's a
+    // remote. Nothing is written without closed issues.
+    fn write_markdown_closed_issues(
+        &self,
+        version: &version::Version,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let issues = self.closed_issues(version);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "### {}\n", self.closed_issues_heading())?;
+        for issue in issues {
+            match self.issue_url(issue) {
+                Some(url) => writeln!(out, "- [#{}]({})", issue, url)?,
+                None => writeln!(out, "- #{}", issue)?,
+            }
+        }
+        writeln!(out)
+    }
+
+    // A commit's markdown bullet rendered with the commit template. The scope is
+    // left empty under scope headings, where it would only repeat the heading.
+    fn write_markdown_bullet(
+        &self,
+        commit: &ParsedCommit,
+        show_scope: bool,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let field = |name: &str| match name {
+            "scope" if show_scope => commit.scope().map_or(String::new(), |scope| {
+                self.markdown_text(&scope).into_owned()
+            }),
+            "message" => self.markdown_message(&commit.message),
+            "hash" => commit.id.clone(),
+            "url" => self.commit_url(commit).unwrap_or_default(),
+            "short_hash" => commit.id.chars().take(SHORT_HASH_LENGTH).collect(),
+            "type" => commit.commit_type.as_str().to_string(),
+            "date" => self.format_date(commit.timestamp),
+            "author" => self.markdown_text(&commit.author).into_owned(),
+            _ => String::new(),
+        };
+
+        writeln!(
+            out,
+            "{}{}{}{}",
+            commit_template::render(&self.commit_template, &field),
+            self.markdown_pull_request(commit),
+            self.signature_marker(commit),
+            self.markdown_commit_date(commit)
+        )?;
+        if let Some(paths) = self.paths_text(commit) {
+            writeln!(out, "  - _{}_", self.markdown_text(&paths))?;
+        }
+        Ok(())
+    }
+
+    // Lists every breaking commit in the rendered versions under one heading, each
+    // linking to the anchor of the version it landed in. Nothing is written when
+    // there are no breaking commits.
+    fn write_breaking_summary(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let mut breaking = Vec::new();
+        for version in versions.iter().filter(|version| !self.is_hidden(version)) {
+            for commit_type in self.rendered_commit_types(version) {
+                breaking.extend(
+                    version.commits_by_type[commit_type]
+                        .iter()
+                        .filter(|commit| commit.breaking)
+                        .map(|commit| (version, commit)),
+                );
+            }
+        }
+
+        if breaking.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "## Breaking Changes\n")?;
+        for (version, commit) in breaking {
+            let message = format!(
+                "{}{}",
+                self.markdown_text(&commit.message),
+                self.markdown_pull_request(commit)
+            );
+            let link = format!(
+                "[{}](#{})",
+                self.version_label(&version.name),
+                version_anchor(&version.name)
+            );
+            if let Some(scope) = commit.scope() {
+                writeln!(
+                    out,
+                    "- **{}**: {} ({})",
+                    self.markdown_text(&scope),
+                    message,
+                    link
+                )?;
+            } else {
+                writeln!(out, "- {} ({})", message, link)?;
+            }
+        }
+        writeln!(out)?;
+
+        Ok(())
+    }
+
+    pub fn write_html_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if !self.minify {
+            self.write_html(versions, out, title)?;
+            return Ok(());
+        }
+
+        let mut html = Vec::new();
+        self.write_html(versions, &mut html, title)?;
+        out.write_all(minify_html(&String::from_utf8_lossy(&html)).as_bytes())?;
+        Ok(())
+    }
+
+    // Writes the indented HTML document (or fragment)
+    fn write_html(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> std::io::Result<()> {
+        let title_level = self.html_heading_level(0);
+        let version_level = self.html_heading_level(1);
+        let type_level = self.html_heading_level(2);
+        let scope_level = self.html_heading_level(3);
+        let standalone = !self.notes_only && !self.fragment;
+        let title = escape_html_text(title);
+
+        // Write HTML header
+        if standalone {
+            write!(
+                out,
+                r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>{}</title>
+    <style>
+{}        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; line-height: 1.5; max-width: 800px; margin: 0 auto; padding: 20px; color: var(--fg); background: var(--bg); }}
+        h{title_level} {{ border-bottom: 1px solid var(--border); padding-bottom: 0.3em; }}
+        h{version_level} {{ margin-top: 24px; margin-bottom: 16px; font-weight: 600; line-height: 1.25; border-bottom: 1px solid var(--border); padding-bottom: 0.3em; }}
+        h{type_level} {{ margin-top: 24px; margin-bottom: 16px; font-weight: 600; line-height: 1.25; }}
+        ul {{ padding-left: 2em; }}
+        li {{ margin: 0.25em 0; }}
+        .diffstat {{ color: var(--muted); }}
+        .paths {{ color: var(--muted); font-size: 0.9em; }}
+        .footer {{ margin-top: 30px; color: var(--muted); font-size: 0.9em; text-align: center; }}
+    </style>
+</head>
+<body>
+"#,
+                title,
+                theme_variables(self.theme),
+            )?;
+        }
+
+        if !self.notes_only {
+            writeln!(out, "    <h{0}>{1}</h{0}>", title_level, title)?;
+            writeln!(
+                out,
+                "    <p>All notable changes to this project will be documented in this file.</p>"
+            )?;
+        }
+
+        for version in versions.iter().filter(|version| !self.is_hidden(version)) {
+            if version.name == "unreleased" {
+                writeln!(out, "    <h{0}>[unreleased]</h{0}>", version_level)?;
+            } else if let Some(date) = version.date {
+                writeln!(
+                    out,
+                    "    <h{0}>[{1}] - {2}</h{0}>",
+                    version_level,
+                    self.html_version_name(&version.name),
+                    self.format_date(date)
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "    <h{0}>[{1}]</h{0}>",
+                    version_level,
+                    self.html_version_name(&version.name)
+                )?;
+            }
+
+            if let Some(summary) = self.summaries.get(&version.name) {
+                writeln!(
+                    out,
+                    r#"    <p class="summary"><em>{}</em></p>"#,
+                    escape_html_text(summary)
+                )?;
+            }
+            if let Some(diff_stat) = version.diff_stat {
+                writeln!(out, r#"    <p class="diffstat">{}</p>"#, diff_stat)?;
+            }
+
+            if self.group_by == GroupBy::Author {
+                for (author, commits) in self.author_groups(version) {
+                    let author_text = escape_html_text(author);
+                    match self.author_url(author) {
+                        Some(url) => writeln!(
+                            out,
+                            r#"    <h{0}><a href="{1}">{2}</a></h{0}>"#,
+                            type_level,
+                            escape_attribute(&url),
+                            author_text
+                        )?,
+                        None => writeln!(out, "    <h{0}>{1}</h{0}>", type_level, author_text)?,
+                    }
+                    writeln!(out, "    <ul>")?;
+                    for commit in commits {
+                        self.write_html_bullet(commit, true, out)?;
+                    }
+                    writeln!(out, "    </ul>")?;
+                }
+                self.write_html_closed_issues(version, type_level, out)?;
+                continue;
+            }
+
+            if let Some(date_sections) = self.unreleased_date_sections(version) {
+                for (date, sections) in date_sections {
+                    writeln!(out, "    <h{0}>{1}</h{0}>", type_level, date)?;
+                    for section in sections {
+                        writeln!(
+                            out,
+                            "    <h{0}>{1}</h{0}>",
+                            scope_level,
+                            escape_html_text(&self.section_heading(&section))
+                        )?;
+                        writeln!(out, "    <ul>")?;
+                        for commit in section.commits {
+                            self.write_html_bullet(commit, true, out)?;
+                        }
+                        writeln!(out, "    </ul>")?;
+                    }
+                }
+                self.write_html_closed_issues(version, type_level, out)?;
+                continue;
+            }
+
+            if self.layout == Layout::ScopeFirst {
+                for (scope, sections) in self.scope_sections(version) {
+                    writeln!(
+                        out,
+                        "    <h{0}>{1}</h{0}>",
+                        type_level,
+                        escape_html_text(scope)
+                    )?;
+                    for section in sections {
+                        writeln!(
+                            out,
+                            "    <h{0}>{1}</h{0}>",
+                            scope_level,
+                            escape_html_text(&self.section_heading(&section))
+                        )?;
+                        writeln!(out, "    <ul>")?;
+                        for commit in section.commits {
+                            self.write_html_bullet(commit, false, out)?;
+                        }
+                        writeln!(out, "    </ul>")?;
+                    }
+                }
+                self.write_html_closed_issues(version, type_level, out)?;
+                continue;
+            }
+
+            for section in self.sections(version) {
+                let commits = &section.commits;
+                writeln!(
+                    out,
+                    "    <h{0}>{1}</h{0}>",
+                    type_level,
+                    escape_html_text(&self.section_heading(&section))
+                )?;
+                if self.group_by_scope {
+                    for (scope, commits) in self.scope_groups(version, commits) {
+                        writeln!(
+                            out,
+                            "    <h{0}>{1}</h{0}>",
+                            scope_level,
+                            escape_html_text(scope)
+                        )?;
+                        writeln!(out, "    <ul>")?;
+
+                        for commit in commits {
+                            self.write_html_bullet(commit, false, out)?;
+                        }
+
+                        writeln!(out, "    </ul>")?;
+                    }
+                } else {
+                    writeln!(out, "    <ul>")?;
+
+                    for commit in commits {
+                        self.write_html_bullet(commit, true, out)?;
+                    }
+
+                    writeln!(out, "    </ul>")?;
+                }
+            }
+
+            self.write_html_closed_issues(version, type_level, out)?;
+        }
+
+        if let Some(footer) = self.footer_text() {
+            writeln!(
+                out,
+                r#"    <div class="footer">Generated by chronicle {}</div>"#,
+                footer
+            )?;
+        }
+
+        if standalone {
+            write!(
+                out,
+                r#"</body>
+</html>
+"#
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // The closed issues section of a version under a heading at `level`
+    fn write_html_closed_issues(
+        &self,
+        version: &version::Version,
+        level: u8,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let issues = self.closed_issues(version);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            out,
+            "    <h{0}>{1}</h{0}>",
+            level,
+            self.closed_issues_heading()
+        )?;
+        writeln!(out, "    <ul>")?;
+        for issue in issues {
+            match self.issue_url(issue) {
+                Some(url) => writeln!(out, r#"        <li><a href="{}">#{}</a></li>"#, url, issue)?,
+                None => writeln!(out, "        <li>#{}</li>", issue)?,
+            }
+        }
+        writeln!(out, "    </ul>")
+    }
+
+    // A commit's list item, with its scope in bold when it has one and
+    // `show_scope` is set (scope headings already name it)
+    fn write_html_bullet(
+        &self,
+        commit: &ParsedCommit,
+        show_scope: bool,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let message = self.html_message(&commit.message);
+        let pr = self.html_pull_request(commit);
+        let marker = self.signature_marker(commit);
+        let date = if self.show_commit_dates {
+            format!(
+                r#" <time datetime="{}">{}</time>"#,
+                commit.timestamp.to_rfc3339(),
+                self.format_date(commit.timestamp)
+            )
+        } else {
+            String::new()
+        };
+        let paths = self
+            .paths_text(commit)
+            .map(|paths| {
+                format!(
+                    r#"<br><span class="paths">{}</span>"#,
+                    escape_html_text(&paths)
+                )
+            })
+            .unwrap_or_default();
+        if let Some(scope) = commit.scope().filter(|_| show_scope) {
+            writeln!(
+                out,
+                "        <li><strong>{}</strong>: {}{}{}{}{}</li>",
+                escape_html_text(&scope),
+                message,
+                pr,
+                marker,
+                date,
+                paths
+            )
+        } else {
+            writeln!(
+                out,
+                "        <li>{}{}{}{}{}</li>",
+                message, pr, marker, date, paths
+            )
+        }
+    }
+
+    /// Writes the changelog in Slack's mrkdwn flavor, which has no headings:
+    /// versions and type sections become bold lines and commits `•` bullets.
+    pub fn write_slack_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if !self.notes_only {
+            writeln!(out, "*{}*", escape_slack(title))?;
+        }
+
+        let versions = versions.iter().filter(|version| !self.is_hidden(version));
+        for (index, version) in versions.enumerate() {
+            // Blank line after the title or the previous version
+            if index > 0 || !self.notes_only {
+                writeln!(out)?;
+            }
+            if version.name == "unreleased" {
+                writeln!(out, "*[unreleased]*")?;
+            } else if let Some(date) = version.date {
+                writeln!(
+                    out,
+                    "*[{}] - {}*",
+                    escape_slack(self.version_label(&version.name)),
+                    self.format_date(date)
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "*[{}]*",
+                    escape_slack(self.version_label(&version.name))
+                )?;
+            }
+
+            if let Some(summary) = self.summaries.get(&version.name) {
+                writeln!(out, "_{}_", escape_slack(summary))?;
+            }
+            if let Some(diff_stat) = version.diff_stat {
+                writeln!(out, "_{}_", diff_stat)?;
+            }
+
+            if self.group_by == GroupBy::Author {
+                for (author, commits) in self.author_groups(version) {
+                    match self.author_url(author) {
+                        Some(url) => writeln!(out, "*<{}|{}>*", url, escape_slack(author))?,
+                        None => writeln!(out, "*{}*", escape_slack(author))?,
+                    }
+                    for commit in commits {
+                        self.write_slack_bullet(commit, out)?;
+                    }
+                }
+                self.write_slack_closed_issues(version, out)?;
+                continue;
+            }
+
+            for section in self.sections(version) {
+                let commits = &section.commits;
+                writeln!(out, "*{}*", section.heading)?;
+
+                if self.group_by_scope {
+                    for (scope, commits) in self.scope_groups(version, commits) {
+                        writeln!(out, "_{}_", escape_slack(scope))?;
+
+                        for commit in commits {
+                            writeln!(
+                                out,
+                                "• {}{}{}",
+                                escape_slack(&self.short_message(&commit.message)),
+                                self.slack_pull_request(commit),
+                                self.signature_marker(commit)
+                            )?;
+                        }
+                    }
+                } else {
+                    for commit in commits {
+                        self.write_slack_bullet(commit, out)?;
+                    }
+                }
+            }
+
+            self.write_slack_closed_issues(version, out)?;
+        }
+
+        Ok(())
+    }
+
+    // The closed issues section of a version, using Slack's <url|text> links
+    fn write_slack_closed_issues(
+        &self,
+        version: &version::Version,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let issues = self.closed_issues(version);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "*{}*", self.closed_issues_heading())?;
+        for issue in issues {
+            match self.issue_url(issue) {
+                Some(url) => writeln!(out, "• <{}|#{}>", url, issue)?,
+                None => writeln!(out, "• #{}", issue)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    // A commit's mrkdwn bullet, with its scope in bold when it has one
+    fn write_slack_bullet(
+        &self,
+        commit: &ParsedCommit,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let message = escape_slack(&self.short_message(&commit.message));
+        let pr = self.slack_pull_request(commit);
+        let marker = self.signature_marker(commit);
+        if let Some(scope) = commit.scope() {
+            writeln!(
+                out,
+                "• *{}*: {}{}{}",
+                escape_slack(&scope),
+                message,
+                pr,
+                marker
+            )
+        } else {
+            writeln!(out, "• {}{}{}", message, pr, marker)
+        }
+    }
+
+    /// Writes the changelog as an AsciiDoc document: the title as the `=` document
+    /// title, versions as `==` sections and type sections as `===` ones, with
+    /// commits as `*` bullets.
+    pub fn write_asciidoc_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if !self.notes_only {
+            writeln!(out, "= {}\n", escape_asciidoc(title))?;
+            writeln!(
+                out,
+                "All notable changes to this project will be documented in this file.\n"
+            )?;
+        }
+
+        for version in versions.iter().filter(|version| !self.is_hidden(version)) {
+            let label = escape_asciidoc(self.version_label(&version.name));
+            // Brackets without a macro name in front of them are plain text
+            if version.name == "unreleased" {
+                writeln!(out, "== [unreleased]\n")?;
+            } else if let Some(date) = version.date {
+                writeln!(out, "== [{}] - {}\n", label, self.format_date(date))?;
+            } else {
+                writeln!(out, "== [{}]\n", label)?;
+            }
+
+            if let Some(summary) = self.summaries.get(&version.name) {
+                writeln!(out, "_{}_\n", escape_asciidoc(summary))?;
+            }
+            if let Some(diff_stat) = version.diff_stat {
+                writeln!(out, "_{}_\n", diff_stat)?;
+            }
+
+            if self.group_by == GroupBy::Author {
+                for (author, commits) in self.author_groups(version) {
+                    match self.author_url(author) {
+                        Some(url) => writeln!(out, "=== {}[{}]\n", url, escape_asciidoc(author))?,
+                        None => writeln!(out, "=== {}\n", escape_asciidoc(author))?,
+                    }
+                    for commit in commits {
+                        self.write_asciidoc_bullet(commit, true, out)?;
+                    }
+                    writeln!(out)?;
+                }
+                self.write_asciidoc_closed_issues(version, out)?;
+                continue;
+            }
+
+            for section in self.sections(version) {
+                let commits = &section.commits;
+                writeln!(
+                    out,
+                    "=== {}\n",
+                    escape_asciidoc(&self.section_heading(&section))
+                )?;
+
+                if self.group_by_scope {
+                    for (scope, commits) in self.scope_groups(version, commits) {
+                        writeln!(out, "==== {}\n", escape_asciidoc(scope))?;
+                        for commit in commits {
+                            self.write_asciidoc_bullet(commit, false, out)?;
+                        }
+                        writeln!(out)?;
+                    }
+                } else {
+                    for commit in commits {
+                        self.write_asciidoc_bullet(commit, true, out)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+
+            self.write_asciidoc_closed_issues(version, out)?;
+        }
+
+        if let Some(footer) = self.footer_text() {
+            writeln!(out, "// {} {}", self.footer_marker, footer)?;
+        }
+
+        Ok(())
+    }
+
+    // The closed issues section of a version, linking each issue when there's a
+    // remote
+    fn write_asciidoc_closed_issues(
+        &self,
+        version: &version::Version,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let issues = self.closed_issues(version);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "=== {}\n", self.closed_issues_heading())?;
+        for issue in issues {
+            match self.issue_url(issue) {
+                Some(url) => writeln!(out, "* {}[#{}]", url, issue)?,
+                None => writeln!(out, "* #{}", issue)?,
+            }
+        }
+        writeln!(out)
+    }
+
+    // A commit's AsciiDoc bullet, with its scope in bold when it has one and
+    // `show_scope` is set (scope headings already name it)
+    fn write_asciidoc_bullet(
+        &self,
+        commit: &ParsedCommit,
+        show_scope: bool,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let message = escape_asciidoc(&self.short_message(&commit.message));
+        let pr = self.asciidoc_pull_request(commit);
+        let marker = self.signature_marker(commit);
+        let date = if self.show_commit_dates {
+            format!(" _{}_", self.format_date(commit.timestamp))
+        } else {
+            String::new()
+        };
+        match commit.scope().filter(|_| show_scope) {
+            Some(scope) => writeln!(
+                out,
+                "* *{}*: {}{}{}{}",
+                escape_asciidoc(&scope),
+                message,
+                pr,
+                marker,
+                date
+            )?,
+            None => writeln!(out, "* {}{}{}{}", message, pr, marker, date)?,
+        }
+        if let Some(paths) = self.paths_text(commit) {
+            writeln!(out, "** _{}_", escape_asciidoc(&paths))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the changelog as a reStructuredText document: the title underlined
+    /// with `=`, versions with `-` and type sections with `~`, with commits as
+    /// `- ` bullets.
+    pub fn write_rst_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        if !self.notes_only {
+            write_rst_heading(out, &escape_rst(title), '=')?;
+            writeln!(
+                out,
+                "All notable changes to this project will be documented in this file.\n"
+            )?;
+        }
+
+        for version in versions.iter().filter(|version| !self.is_hidden(version)) {
+            let label = escape_rst(self.version_label(&version.name));
+            let heading = if version.name == "unreleased" {
+                "[unreleased]".to_string()
+            } else if let Some(date) = version.date {
+                format!("[{}] - {}", label, self.format_date(date))
+            } else {
+                format!("[{}]", label)
+            };
+            write_rst_heading(out, &heading, '-')?;
+
+            if let Some(summary) = self.summaries.get(&version.name) {
+                writeln!(out, "*{}*\n", escape_rst(summary))?;
+            }
+            if let Some(diff_stat) = version.diff_stat {
+                writeln!(out, "*{}*\n", diff_stat)?;
+            }
+
+            if self.group_by == GroupBy::Author {
+                for (author, commits) in self.author_groups(version) {
+                    let heading = match self.author_url(author) {
+                        Some(url) => format!("`{} <{}>`__", escape_rst(author), url),
+                        None => escape_rst(author),
+                    };
+                    write_rst_heading(out, &heading, '~')?;
+                    for commit in commits {
+                        self.write_rst_bullet(commit, true, out)?;
+                    }
+                    writeln!(out)?;
+                }
+                self.write_rst_closed_issues(version, out)?;
+                continue;
+            }
+
+            for section in self.sections(version) {
+                let commits = &section.commits;
+                write_rst_heading(out, &escape_rst(&self.section_heading(&section)), '~')?;
+
+                if self.group_by_scope {
+                    for (scope, commits) in self.scope_groups(version, commits) {
+                        write_rst_heading(out, &escape_rst(scope), '^')?;
+                        for commit in commits {
+                            self.write_rst_bullet(commit, false, out)?;
+                        }
+                        writeln!(out)?;
+                    }
+                } else {
+                    for commit in commits {
+                        self.write_rst_bullet(commit, true, out)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+
+            self.write_rst_closed_issues(version, out)?;
+        }
+
+        if let Some(footer) = self.footer_text() {
+            writeln!(out, ".. {} {}", self.footer_marker, footer)?;
+        }
+
+        Ok(())
+    }
+
+    // The closed issues section of a version, linking each issue when there's a
+    // remote
+    fn write_rst_closed_issues(
+        &self,
+        version: &version::Version,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let issues = self.closed_issues(version);
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        write_rst_heading(out, self.closed_issues_heading(), '~')?;
+        for issue in issues {
+            match self.issue_url(issue) {
+                Some(url) => writeln!(out, "- `#{} <{}>`__", issue, url)?,
+                None => writeln!(out, "- #{}", issue)?,
+            }
+        }
+        writeln!(out)
+    }
+
+    // A commit's reStructuredText bullet, with its scope in bold when it has one
+    // and `show_scope` is set (scope headings already name it). Changed paths go
+    // in a second paragraph of the item.
+    fn write_rst_bullet(
+        &self,
+        commit: &ParsedCommit,
+        show_scope: bool,
+        out: &mut impl Write,
+    ) -> std::io::Result<()> {
+        let message = escape_rst(&self.short_message(&commit.message));
+        let pr = self.rst_pull_request(commit);
+        let marker = self.signature_marker(commit);
+        let date = if self.show_commit_dates {
+            format!(" *{}*", self.format_date(commit.timestamp))
+        } else {
+            String::new()
+        };
+        match commit.scope().filter(|_| show_scope) {
+            Some(scope) => writeln!(
+                out,
+                "- **{}**: {}{}{}{}",
+                escape_rst(&scope),
+                message,
+                pr,
+                marker,
+                date
+            )?,
+            // Escaped so a message like "1. step" isn't read as a nested list
+            None if message
+                .starts_with(|c: char| c.is_ascii_punctuation() || c.is_ascii_digit()) =>
+            {
+                writeln!(out, "- \\{}{}{}{}", message, pr, marker, date)?
+            }
+            None => writeln!(out, "- {}{}{}{}", message, pr, marker, date)?,
+        }
+        if let Some(paths) = self.paths_text(commit) {
+            writeln!(out, "\n  *{}*\n", escape_rst(&paths))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the changelog as a YAML document: the title and the shown versions,
+    /// each with its ISO 8601 date (null when unreleased) and its sections of
+    /// commits. Strings are written as JSON literals, which YAML reads as
+    /// double-quoted scalars.
+    pub fn write_yaml_changelog(
+        &self,
+        versions: &[version::Version],
+        out: &mut impl Write,
+        title: &str,
+    ) -> Result<()> {
+        writeln!(out, "title: {}", json::string(title))?;
+
+        let shown: Vec<&version::Version> = versions
+            .iter()
+            .filter(|version| !self.is_hidden(version))
+            .collect();
+        if shown.is_empty() {
+            writeln!(out, "versions: []")?;
+            return Ok(());
+        }
+
+        writeln!(out, "versions:")?;
+        for version in shown {
+            writeln!(out, "  - name: {}", json::string(&version.name))?;
+            writeln!(
+                out,
+                "    date: {}",
+                version
+                    .date
+                    .map_or("null".to_string(), |date| json::string(&date.to_rfc3339()))
+            )?;
+            // Empty versions of unreachable tags have no sections
+            let sections = self.sections(version);
+            if sections.is_empty() {
+                writeln!(out, "    sections: []")?;
+                continue;
+            }
+            writeln!(out, "    sections:")?;
+            for section in sections {
+                let types: Vec<String> = section
+                    .types
+                    .iter()
+                    .map(|commit_type| commit_type.as_str().to_string())
+                    .collect();
+                writeln!(out, "      - types: {}", json::string_array(&types))?;
+                writeln!(out, "        heading: {}", json::string(section.heading))?;
+                writeln!(out, "        commits:")?;
+                for commit in section.commits {
+                    writeln!(out, "          - id: {}", json::string(&commit.id))?;
+                    writeln!(
+                        out,
+                        "            type: {}",
+                        json::string(commit.commit_type.as_str())
+                    )?;
+                    writeln!(
+                        out,
+                        "            scopes: {}",
+                        json::string_array(&commit.scopes)
+                    )?;
+                    writeln!(
+                        out,
+                        "            message: {}",
+                        json::string(&commit.message)
+                    )?;
+                    writeln!(
+                        out,
+                        "            timestamp: {}",
+                        json::string(&commit.timestamp.to_rfc3339())
+                    )?;
+                    writeln!(out, "            author: {}", json::string(&commit.author))?;
+                    writeln!(out, "            breaking: {}", commit.breaking)?;
+                    writeln!(out, "            signed: {}", commit.signed)?;
+                    writeln!(
+                        out,
+                        "            pr: {}",
+                        commit.pr.map_or("null".to_string(), |pr| pr.to_string())
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes one JSON object per commit, each on its own line, as commits are
+    /// streamed from the repository. The version a commit belongs to is given by
+    /// its `version` and `version_date` fields. Lines follow walk order (newest
+    /// first) regardless of the sort order.
+    pub fn write_ndjson_changelog(&self, out: &mut impl Write) -> Result<()> {
+        let mut out = LineWriter::new(out);
+
+        for versioned in self.iter_commits()? {
+            let mut versioned = versioned?;
+            let diff_stat = self.read_changes(&mut versioned.commit)?;
+            writeln!(&mut out, "{}", ndjson_line(&versioned, diff_stat))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes `date` relative to `now`, e.g. "3 months ago", stepping through
+/// days, weeks, months and years
+pub fn relative_date(date: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let days = (now - date).num_days();
+
+    let (amount, unit) = match days {
+        ..=0 => return "today".to_string(),
+        1..=6 => (days, "day"),
+        7..=29 => (days / 7, "week"),
+        30..=364 => (days / 30, "month"),
+        _ => (days / 365, "year"),
+    };
+
+    if amount == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", amount, unit)
+    }
+}
+
+// Serializes a commit and its version as a single-line JSON object, with the
+// commit's diff stat when it was read
+fn ndjson_line(versioned: &VersionedCommit, diff_stat: Option<DiffStat>) -> String {
+    let commit = &versioned.commit;
+    let count = |count: Option<usize>| count.map_or("null".to_string(), |count| count.to_string());
+
+    format!(
+        r#"{{"version":{},"version_date":{},"id":{},"type":{},"scopes":{},"scope":{},"message":{},"timestamp":{},"signed":{},"breaking":{},"author":{},"closed_issues":[{}],"pr":{},"reverts":{},"body_items":{},"paths":{},"files_changed":{},"insertions":{},"deletions":{}}}"#,
+        json::string(&versioned.version),
+        versioned
+            .version_date
+            .map_or("null".to_string(), |date| json::string(&date.to_rfc3339())),
+        json::string(&commit.id),
+        json::string(commit.commit_type.as_str()),
+        json::string_array(&commit.scopes),
+        commit
+            .scope()
+            .map_or("null".to_string(), |scope| json::string(&scope)),
+        json::string(&commit.message),
+        json::string(&commit.timestamp.to_rfc3339()),
+        commit.signed,
+        commit.breaking,
+        json::string(&commit.author),
+        commit
+            .closed_issues
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+        commit.pr.map_or("null".to_string(), |pr| pr.to_string()),
+        commit
+            .reverts
+            .as_deref()
+            .map_or("null".to_string(), json::string),
+        json::string_array(&commit.body_items),
+        json::string_array(&commit.paths),
+        count(diff_stat.map(|diff_stat| diff_stat.files_changed)),
+        count(diff_stat.map(|diff_stat| diff_stat.insertions)),
+        count(diff_stat.map(|diff_stat| diff_stat.deletions))
+    )
+}
+
+/// Anchor id for a version heading: the lowercased name with runs of anything
+/// but letters and digits turned into hyphens, e.g. "v1.0.0" -> "v1-0-0"
+pub fn version_anchor(name: &str) -> String {
+    let mut anchor = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            anchor.extend(c.to_lowercase());
+        } else if !anchor.is_empty() && !anchor.ends_with('-') {
+            anchor.push('-');
+        }
+    }
+
+    anchor.trim_end_matches('-').to_string()
+}
+
+// Places an empty version for an unreachable tag among the newest-first versions,
+// ahead of the first tagged version older than it. Undated tags go last.
+fn insert_by_date(versions: &mut Vec<version::Version>, version: version::Version) {
+    let index = version
+        .date
+        .and_then(|date| {
+            versions
+                .iter()
+                .position(|v| v.date.is_some_and(|existing| existing < date))
+        })
+        .unwrap_or(versions.len());
+
+    versions.insert(index, version);
+}
+
+/// Characters backslash-escaped by `escape_markdown`
+pub const MARKDOWN_SPECIAL_CHARS: &[char] =
+    &['\\', '`', '*', '_', '[', ']', '<', '>', '#', '|', '~'];
+
+/// Backslash-escapes markdown metacharacters so `text` renders literally
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if MARKDOWN_SPECIAL_CHARS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Shortens `message` to at most `max` characters, ending with `…` after the
+/// last whole word that fits, or returns None when it already fits. A first word
+/// longer than that is cut mid-word. Lengths are counted in characters, so a
+/// multibyte character is never split.
+pub fn truncate_subject(message: &str, max: usize) -> Option<String> {
+    if message.chars().count() <= max {
+        return None;
+    }
+
+    // Room for the ellipsis
+    let end = message
+        .char_indices()
+        .nth(max.saturating_sub(1))
+        .map_or(message.len(), |(index, _)| index);
+    let kept = &message[..end];
+    let kept = if message[end..].starts_with(char::is_whitespace) {
+        kept
+    } else {
+        kept.rfind(char::is_whitespace)
+            .map(|index| &kept[..index])
+            .filter(|words| !words.trim_end().is_empty())
+            .unwrap_or(kept)
+    };
+
+    Some(format!("{}…", kept.trim_end()))
+}
+
+// Escapes free-form text, such as a summary or an author's name, for an HTML
+// text node
+fn escape_html_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Escapes text for a double-quoted HTML attribute value
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Removes a leading emoji (any first word without letters or digits) from a
+/// heading, e.g. "🚀 Features" -> "Features"
+pub fn strip_emoji(heading: &str) -> &str {
+    match heading.split_once(' ') {
+        Some((first, rest)) if !first.chars().any(char::is_alphanumeric) => rest.trim_start(),
+        _ => heading,
+    }
+}
+
+// The sections cut down to the given commits, leaving out those without any
+fn sections_with<'a>(sections: &[Section<'a>], commits: &[&ParsedCommit]) -> Vec<Section<'a>> {
+    sections
+        .iter()
+        .map(|section| Section {
+            merge: section.merge,
+            types: section.types.clone(),
+            heading: section.heading,
+            commits: section
+                .commits
+                .iter()
+                .copied()
+                .filter(|commit| commits.iter().any(|other| std::ptr::eq(*other, *commit)))
+                .collect(),
+        })
+        .filter(|section| !section.commits.is_empty())
+        .collect()
+}
+
+// One section of a version: the commits of a type, or of the types merged by a
+// `type_merges` rule, the index of which is `merge`
+struct Section<'a> {
+    merge: Option<usize>,
+    types: Vec<&'a CommitType>,
+    heading: &'a str,
+    commits: Vec<&'a ParsedCommit>,
+}
+
+// Moves the commits of each prerelease version, newest first, into the stable
+// version released after it, leaving out commits already there. Prereleases
+// without a stable version after them are kept as they are.
+fn group_prereleases(versions: Vec<version::Version>) -> Vec<version::Version> {
+    let mut grouped: Vec<version::Version> = Vec::with_capacity(versions.len());
+    let mut stable = None;
+
+    for version in versions {
+        if version.name == "unreleased" {
+            grouped.push(version);
+            continue;
+        }
+        if !version::is_prerelease(&version.name) {
+            stable = Some(grouped.len());
+            grouped.push(version);
+            continue;
+        }
+        let Some(index) = stable else {
+            grouped.push(version);
+            continue;
+        };
+
+        let stable = &mut grouped[index];
+        if let Some(diff_stat) = version.diff_stat {
+            stable.diff_stat.get_or_insert_default().add(diff_stat);
+        }
+        for (commit_type, commits) in version.commits_by_type {
+            let bucket = stable.commits_by_type.entry(commit_type).or_default();
+            for commit in commits {
+                // Expanded body items share their commit's id
+                let known = bucket
+                    .iter()
+                    .any(|other| other.id == commit.id && other.message == commit.message);
+                if !known {
+                    bucket.push(commit);
+                }
+            }
+        }
+    }
+
+    grouped
+}
+
+// The commit types present in a version, in the order their sections are rendered
+fn ordered_commit_types(version: &version::Version) -> Vec<&CommitType> {
+    let mut commit_types: Vec<&CommitType> = version.commits_by_type.keys().collect();
+    commit_types.sort_by_key(|commit_type| commit_type.section_order());
+
+    commit_types
+}
+
+/// Strips the indentation and line breaks between tags of the HTML written by
+/// chronicle and collapses the whitespace of its stylesheet. Text on a line
+/// is kept as is.
+pub fn minify_html(html: &str) -> String {
+    let mut minified = String::with_capacity(html.len());
+    let mut in_style = false;
+
+    for line in html.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if line.starts_with("</style>") {
+            in_style = false;
+        }
+
+        let line = if in_style {
+            minify_css(line)
+        } else {
+            line.to_string()
+        };
+        // Line breaks between tags or style rules are insignificant, but between
+        // two runs of text they separate words
+        let joins_text = !minified.ends_with(['>', '}']) && !line.starts_with('<');
+        if !minified.is_empty() && joins_text {
+            minified.push(' ');
+        }
+        minified.push_str(&line);
+
+        if line.starts_with("<style") {
+            in_style = true;
+        }
+    }
+
+    minified.push('\n');
+    minified
+}
+
+// Drops the whitespace around CSS punctuation and collapses the rest to single
+// spaces, leaving quoted strings alone
+fn minify_css(css: &str) -> String {
+    const PUNCTUATION: [char; 5] = ['{', '}', ';', ':', ','];
+    let mut minified = String::with_capacity(css.len());
+    let mut quote = None;
+    let mut chars = css.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c.is_whitespace() => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                let before = minified.ends_with(PUNCTUATION);
+                let after = chars.peek().is_some_and(|next| PUNCTUATION.contains(next));
+                if !before && !after && chars.peek().is_some() {
+                    minified.push(' ');
+                }
+                continue;
+            }
+            None => {}
+        }
+        minified.push(c);
+    }
+
+    minified
+}
+
+/// Characters backslash-escaped by `escape_rst`
+pub const RST_SPECIAL_CHARS: &[char] = &['\\', '*', '`', '_', '|'];
+
+/// Backslash-escapes the characters reStructuredText reads as inline markup,
+/// hyperlink references or substitutions, so `text` renders literally
+pub fn escape_rst(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if RST_SPECIAL_CHARS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Columns `text` takes up in a monospace font, as reStructuredText measures
+/// section titles: East Asian wide characters and emoji take two. Any character
+/// from U+1100 on is counted as wide, so the width is never less than the real
+/// one and an underline this long is always long enough.
+pub fn rst_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c >= '\u{1100}' { 2 } else { 1 })
+        .sum()
+}
+
+// Writes a section title with an underline of `underline` characters as wide as
+// the title, followed by a blank line
+fn write_rst_heading(out: &mut impl Write, title: &str, underline: char) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{}\n{}\n",
+        title,
+        underline.to_string().repeat(rst_width(title))
+    )
+}
+
+/// Characters `escape_asciidoc` replaces with character references
+pub const ASCIIDOC_SPECIAL_CHARS: &[char] =
+    &['*', '_', '`', '#', '^', '~', '+', '{', '}', '[', ']'];
+
+/// Replaces the characters AsciiDoc reads as inline formatting, attribute
+/// references or macros with numeric character references, so `text` renders
+/// literally. A backslash would only escape some of them in some positions.
+/// `<`, `>` and `&` are left to AsciiDoc, which escapes them itself.
+pub fn escape_asciidoc(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if ASCIIDOC_SPECIAL_CHARS.contains(&c) {
+            escaped.push_str(&format!("&#{};", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+// Escapes the characters Slack treats as control sequences in mrkdwn text
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Changed file paths cut down to their first component, directories keeping
+// their trailing slash, sorted and without duplicates
+fn top_level_paths(paths: Vec<String>) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| match path.split_once('/') {
+            Some((directory, _)) => format!("{}/", directory),
+            None => path.clone(),
+        })
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+// Groups commits by scope in alphabetical order, cutting path-like scopes down to
+// their first `depth` segments (0 keeps the whole scope). A commit with several
+// scopes is listed once under each of their groups; scope-less commits go under
+// `no_scope_label`.
+fn group_by_scope<'a>(
+    commits: &[&'a ParsedCommit],
+    no_scope_label: &'a str,
+    depth: usize,
+) -> Vec<(&'a str, Vec<&'a ParsedCommit>)> {
+    let mut groups: BTreeMap<&str, Vec<&ParsedCommit>> = BTreeMap::new();
+
+    for &commit in commits {
+        if commit.scopes.is_empty() {
+            groups.entry(no_scope_label).or_default().push(commit);
+        }
+        for scope in &commit.scopes {
+            let group = groups.entry(scope_prefix(scope, depth)).or_default();
+            if !group.last().is_some_and(|last| std::ptr::eq(*last, commit)) {
+                group.push(commit);
+            }
+        }
+    }
+
+    groups.into_iter().collect()
+}
+
+// Replaces the groups of the `small` scopes with one group under `label`, listed
+// last (or added to a scope group of that name), holding their commits once each
+// in the order of `commits`
+fn fold_scopes<'a>(
+    groups: Vec<(&'a str, Vec<&'a ParsedCommit>)>,
+    commits: &[&'a ParsedCommit],
+    small: &HashSet<String>,
+    label: &'a str,
+) -> Vec<(&'a str, Vec<&'a ParsedCommit>)> {
+    let (folded, mut kept): (Vec<_>, Vec<_>) = groups
+        .into_iter()
+        .partition(|(scope, _)| small.contains(*scope));
+    let in_folded = |commit: &ParsedCommit| {
+        folded
+            .iter()
+            .flat_map(|(_, commits)| commits)
+            .any(|other| std::ptr::eq(*other, commit))
+    };
+    let catch_all: Vec<&ParsedCommit> = commits
+        .iter()
+        .copied()
+        .filter(|commit| in_folded(commit))
+        .collect();
+    if catch_all.is_empty() {
+        return kept;
+    }
+
+    match kept.iter_mut().find(|(scope, _)| *scope == label) {
+        Some((_, commits)) => {
+            for commit in catch_all {
+                if !commits.iter().any(|other| std::ptr::eq(*other, commit)) {
+                    commits.push(commit);
+                }
+            }
+        }
+        None => kept.push((label, catch_all)),
+    }
+
+    kept
+}
+
+/// The first `depth` `/`-separated segments of a scope, e.g. "api" for
+/// "api/v2" at depth 1. A depth of 0 returns the whole scope.
+pub fn scope_prefix(scope: &str, depth: usize) -> &str {
+    if depth == 0 {
+        return scope;
+    }
+
+    match scope.match_indices('/').nth(depth - 1) {
+        Some((index, _)) => &scope[..index],
+        None => scope,
+    }
+}
+
+const LIGHT_PALETTE: &str = "--fg: #24292e; --bg: #ffffff; --border: #eaecef; --muted: #6a737d;";
+const DARK_PALETTE: &str = "--fg: #c9d1d9; --bg: #0d1117; --border: #30363d; --muted: #8b949e;";
+
+// Emits the CSS custom properties used by the HTML stylesheet for the given theme
+fn theme_variables(theme: Theme) -> String {
+    match theme {
+        Theme::Light => format!("        :root {{ {} }}\n", LIGHT_PALETTE),
+        Theme::Dark => format!("        :root {{ {} }}\n", DARK_PALETTE),
+        Theme::Auto => format!(
+            "        :root {{ {} }}\n        @media (prefers-color-scheme: dark) {{ :root {{ {} }} }}\n",
+            LIGHT_PALETTE, DARK_PALETTE
+        ),
     }
 }
@@ -0,0 +1,849 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use crate::{
+    DateGrouping, GroupBy, Layout, LineEnding, Prerelease, ScopeSort, SortOrder, Theme,
+    VersionDateSource,
+    changelog_generator::{
+        ChangelogGenerator, CommitTypeDefault, DEFAULT_COMMIT_PATTERN, DEFAULT_DATE_FORMAT,
+        DEFAULT_VERSION_PATTERN, FOOTER_MARKER, NO_SCOPE_LABEL, SMALL_SCOPE_LABEL, ScopeCollapse,
+        Since,
+    },
+    commit_cache::{CommitCache, DEFAULT_CACHE_DIR_NAME},
+    commit_template::DEFAULT_COMMIT_TEMPLATE,
+    commit_type::{CommitType, TypeMerge},
+    document_template::Template,
+    error::Result,
+    git_provider::{GitError, GitProvider},
+    git2_provider::Git2Provider,
+    locale,
+    remote::{DEFAULT_COMMIT_URL_TEMPLATE, DEFAULT_TAG_URL_TEMPLATE},
+};
+
+/// Fluent builder for `ChangelogGenerator`. Every setting has a default matching
+/// the CLI defaults, so only the options that differ need to be set.
+pub struct ChangelogGeneratorBuilder {
+    version_pattern: Option<String>,
+    commit_pattern: Option<String>,
+    sort_order: SortOrder,
+    date_format: String,
+    theme: Theme,
+    footer: bool,
+    footer_timestamp: bool,
+    describe_footer: bool,
+    footer_marker: String,
+    scopes: Vec<String>,
+    include_types: Vec<CommitType>,
+    exclude_types: Vec<CommitType>,
+    unreleased_requires: Vec<CommitType>,
+    group_by_scope: bool,
+    section_counts: bool,
+    no_scope_label: String,
+    min_scope_size: Option<usize>,
+    small_scope_label: String,
+    scope_sort: ScopeSort,
+    scope_depth: usize,
+    group_by: GroupBy,
+    layout: Layout,
+    commit_template: String,
+    document_template: Option<Template>,
+    closed_issues: bool,
+    empty_as_other: bool,
+    link_prs: bool,
+    relative_dates: bool,
+    now: Option<DateTime<Utc>>,
+    include_unreachable_tags: bool,
+    require_full_history: bool,
+    since: Option<Since>,
+    prerelease: Prerelease,
+    group_prereleases_under_stable: bool,
+    version_date_source: VersionDateSource,
+    rolling_tags: Vec<String>,
+    initial_version: Option<String>,
+    limit: Option<usize>,
+    reverse_within_version: bool,
+    jobs: usize,
+    diffstat: bool,
+    show_paths: bool,
+    expand_body_bullets: bool,
+    max_paths: usize,
+    escape_markdown: bool,
+    ignore_patterns: Vec<String>,
+    cache: bool,
+    cache_dir: Option<PathBuf>,
+    show_signatures: bool,
+    show_commit_dates: bool,
+    max_subject_length: Option<usize>,
+    commit_type_default: CommitTypeDefault,
+    unreleased_date_grouping: Option<DateGrouping>,
+    utc: bool,
+    tag_url_template: String,
+    commit_url_template: String,
+    author_url_template: Option<String>,
+    author_logins: HashMap<String, String>,
+    summaries: HashMap<String, String>,
+    remote: Option<String>,
+    hide_other: bool,
+    other_heading: Option<String>,
+    locale: Option<String>,
+    type_merges: Vec<TypeMerge>,
+    scope_collapses: Vec<ScopeCollapse>,
+    emoji: bool,
+    breaking_summary: bool,
+    normalize_versions: bool,
+    notes_only: bool,
+    heading_base: u8,
+    fragment: bool,
+    minify: bool,
+    line_ending: LineEnding,
+    bom: bool,
+}
+
+impl Default for ChangelogGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            version_pattern: None,
+            commit_pattern: None,
+            sort_order: SortOrder::Newest,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            theme: Theme::Light,
+            footer: true,
+            footer_timestamp: false,
+            describe_footer: false,
+            footer_marker: FOOTER_MARKER.to_string(),
+            scopes: Vec::new(),
+            include_types: Vec::new(),
+            exclude_types: Vec::new(),
+            unreleased_requires: Vec::new(),
+            group_by_scope: false,
+            section_counts: false,
+            no_scope_label: NO_SCOPE_LABEL.to_string(),
+            min_scope_size: None,
+            small_scope_label: SMALL_SCOPE_LABEL.to_string(),
+            scope_sort: ScopeSort::Alpha,
+            scope_depth: 0,
+            group_by: GroupBy::Type,
+            layout: Layout::TypeFirst,
+            commit_template: DEFAULT_COMMIT_TEMPLATE.to_string(),
+            document_template: None,
+            closed_issues: false,
+            empty_as_other: false,
+            link_prs: false,
+            relative_dates: false,
+            now: None,
+            include_unreachable_tags: false,
+            require_full_history: false,
+            since: None,
+            prerelease: Prerelease::Include,
+            group_prereleases_under_stable: false,
+            version_date_source: VersionDateSource::Tagger,
+            rolling_tags: Vec::new(),
+            initial_version: None,
+            limit: None,
+            reverse_within_version: false,
+            jobs: 1,
+            diffstat: false,
+            show_paths: false,
+            expand_body_bullets: false,
+            max_paths: 5,
+            escape_markdown: false,
+            ignore_patterns: Vec::new(),
+            cache: false,
+            cache_dir: None,
+            show_signatures: false,
+            show_commit_dates: false,
+            max_subject_length: None,
+            commit_type_default: CommitTypeDefault::Type(CommitType::Other),
+            unreleased_date_grouping: None,
+            utc: false,
+            tag_url_template: DEFAULT_TAG_URL_TEMPLATE.to_string(),
+            commit_url_template: DEFAULT_COMMIT_URL_TEMPLATE.to_string(),
+            author_url_template: None,
+            author_logins: HashMap::new(),
+            summaries: HashMap::new(),
+            remote: None,
+            hide_other: false,
+            other_heading: None,
+            locale: None,
+            type_merges: Vec::new(),
+            scope_collapses: Vec::new(),
+            emoji: true,
+            breaking_summary: false,
+            normalize_versions: false,
+            notes_only: false,
+            heading_base: 1,
+            fragment: false,
+            minify: false,
+            line_ending: LineEnding::Lf,
+            bom: false,
+        }
+    }
+}
+
+impl ChangelogGeneratorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Regex matched against tag names to detect versions
+    pub fn version_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.version_pattern = Some(pattern.into());
+        self
+    }
+
+    /// Regex with `type`, `scope` and `message` groups used to parse commit subjects
+    pub fn commit_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.commit_pattern = Some(pattern.into());
+        self
+    }
+
+    pub fn sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
+        self
+    }
+
+    /// `chrono` format string used for version dates
+    pub fn date_format(mut self, date_format: impl Into<String>) -> Self {
+        self.date_format = date_format.into();
+        self
+    }
+
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn footer(mut self, footer: bool) -> Self {
+        self.footer = footer;
+        self
+    }
+
+    pub fn footer_timestamp(mut self, footer_timestamp: bool) -> Self {
+        self.footer_timestamp = footer_timestamp;
+        self
+    }
+
+    /// Record in the footer the commit the changelog was generated from, as
+    /// `git describe --tags` names it, falling back to its short id without tags
+    pub fn describe_footer(mut self, describe_footer: bool) -> Self {
+        self.describe_footer = describe_footer;
+        self
+    }
+
+    pub fn footer_marker(mut self, marker: impl Into<String>) -> Self {
+        self.footer_marker = marker.into();
+        self
+    }
+
+    /// Only keep commits whose scope matches one of `scopes` (case-insensitive).
+    /// Use `"none"` to keep commits without a scope.
+    pub fn scopes<I, S>(mut self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only keep commits of these types
+    pub fn include_types(mut self, types: impl IntoIterator<Item = CommitType>) -> Self {
+        self.include_types = types.into_iter().collect();
+        self
+    }
+
+    /// Drop commits of these types
+    pub fn exclude_types(mut self, types: impl IntoIterator<Item = CommitType>) -> Self {
+        self.exclude_types = types.into_iter().collect();
+        self
+    }
+
+    /// Only show the unreleased section when it has a commit of one of these types
+    pub fn unreleased_requires(mut self, types: impl IntoIterator<Item = CommitType>) -> Self {
+        self.unreleased_requires = types.into_iter().collect();
+        self
+    }
+
+    /// Nest commits under a sub-heading per scope within each type section
+    pub fn group_by_scope(mut self, group_by_scope: bool) -> Self {
+        self.group_by_scope = group_by_scope;
+        self
+    }
+
+    /// Append the number of commits listed in each type section to its heading
+    /// in markdown and HTML, e.g. "🚀 Features (5)"
+    pub fn section_counts(mut self, section_counts: bool) -> Self {
+        self.section_counts = section_counts;
+        self
+    }
+
+    /// Heading for commits without a scope when grouping by scope
+    pub fn no_scope_label(mut self, label: impl Into<String>) -> Self {
+        self.no_scope_label = label.into();
+        self
+    }
+
+    /// When grouping by scope, list the scopes with fewer than `min` commits in a
+    /// version together in one group, last, instead of in groups of their own
+    pub fn min_scope_size(mut self, min: usize) -> Self {
+        self.min_scope_size = Some(min);
+        self
+    }
+
+    /// Heading of the group that `min_scope_size` folds small scopes into
+    pub fn small_scope_label(mut self, label: impl Into<String>) -> Self {
+        self.small_scope_label = label.into();
+        self
+    }
+
+    /// Order of the scope groups when grouping by scope
+    pub fn scope_sort(mut self, scope_sort: ScopeSort) -> Self {
+        self.scope_sort = scope_sort;
+        self
+    }
+
+    /// Group path-like scopes such as `api/v2` by their first `depth` segments
+    /// when grouping by scope; 0 groups by the whole scope
+    pub fn scope_depth(mut self, depth: usize) -> Self {
+        self.scope_depth = depth;
+        self
+    }
+
+    /// Section each version by commit type (the default) or by author
+    pub fn group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Nest scope sections inside type sections (the default), or type sections
+    /// inside a section per scope in markdown and HTML
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Template for markdown bullets, with placeholders such as `{scope}`,
+    /// `{message}` and `{short_hash}`; see the `commit_template` module
+    pub fn commit_template(mut self, template: impl Into<String>) -> Self {
+        self.commit_template = template.into();
+        self
+    }
+
+    /// Render the changelog through a document template instead of the built-in
+    /// markdown, HTML and Slack writers. NDJSON output is not affected.
+    pub fn document_template(mut self, template: Template) -> Self {
+        self.document_template = Some(template);
+        self
+    }
+
+    /// List the issues closed by each version's commits (`Closes #12`, `Fixes #3`
+    /// or `Resolves #7` footers) in a section of their own
+    pub fn closed_issues(mut self, closed_issues: bool) -> Self {
+        self.closed_issues = closed_issues;
+        self
+    }
+
+    /// List commits of no known type, such as those the commit pattern doesn't
+    /// match, under another type than Other, or drop them
+    pub fn commit_type_default(mut self, commit_type_default: CommitTypeDefault) -> Self {
+        self.commit_type_default = commit_type_default;
+        self
+    }
+
+    /// Move commits whose pattern matched with an empty message, such as a bare
+    /// `feat:`, to the Other section with their whole subject
+    pub fn empty_as_other(mut self, empty_as_other: bool) -> Self {
+        self.empty_as_other = empty_as_other;
+        self
+    }
+
+    /// List each `- ` item in a commit's body as a bullet of its own, in place of
+    /// the subject, for commits that bundle several changes
+    pub fn expand_body_bullets(mut self, expand_body_bullets: bool) -> Self {
+        self.expand_body_bullets = expand_body_bullets;
+        self
+    }
+
+    /// Link the pull request numbers of squash-merged commits, such as the `#12`
+    /// of "feat: add api (#12)", to their pages on the remote
+    pub fn link_prs(mut self, link_prs: bool) -> Self {
+        self.link_prs = link_prs;
+        self
+    }
+
+    /// Show version dates relative to now (or the reference date), e.g. "3 months ago"
+    pub fn relative_dates(mut self, relative_dates: bool) -> Self {
+        self.relative_dates = relative_dates;
+        self
+    }
+
+    /// Time used as now for relative dates and the footer timestamp, instead of
+    /// `Utc::now()` when the generator is built, for reproducible output
+    pub fn now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Add an empty version for each tag whose commit is outside the walked history
+    pub fn include_unreachable_tags(mut self, include_unreachable_tags: bool) -> Self {
+        self.include_unreachable_tags = include_unreachable_tags;
+        self
+    }
+
+    /// Refuse to build a generator for a shallow clone, whose changelog would
+    /// silently stop at the clone depth
+    pub fn require_full_history(mut self, require_full_history: bool) -> Self {
+        self.require_full_history = require_full_history;
+        self
+    }
+
+    /// Only walk the commits after the one tag `name` points at, up to HEAD
+    pub fn since_tag(mut self, name: impl Into<String>) -> Self {
+        self.since = Some(Since::Tag(name.into()));
+        self
+    }
+
+    /// Only walk the commits after the most recent tag matching the version
+    /// pattern, i.e. the changes since the last release
+    pub fn since_last_tag(mut self) -> Self {
+        self.since = Some(Since::LastTag);
+        self
+    }
+
+    /// Only walk the commits of the current branch that `reference` doesn't have,
+    /// stopping at their merge base, for a changelog of a pull request
+    pub fn base(mut self, reference: impl Into<String>) -> Self {
+        self.since = Some(Since::Base(reference.into()));
+        self
+    }
+
+    /// Only process the `limit` most recent commits
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sum up the files changed and lines added and removed by each version's
+    /// commits, diffing every commit against its first parent
+    pub fn diffstat(mut self, diffstat: bool) -> Self {
+        self.diffstat = diffstat;
+        self
+    }
+
+    /// List the top-level directories and files each commit changed under its
+    /// bullet, diffing every commit against its first parent
+    pub fn show_paths(mut self, show_paths: bool) -> Self {
+        self.show_paths = show_paths;
+        self
+    }
+
+    /// Most paths listed under a bullet with `show_paths`; the rest are counted
+    pub fn max_paths(mut self, max_paths: usize) -> Self {
+        self.max_paths = max_paths;
+        self
+    }
+
+    /// Parse commits on this many threads. The changelog is the same for any
+    /// number; 1 (the default) parses them as they are walked.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// List commits oldest first within each version, whatever the sort order of
+    /// the versions themselves
+    pub fn reverse_within_version(mut self, reverse: bool) -> Self {
+        self.reverse_within_version = reverse;
+        self
+    }
+
+    /// Backslash-escape markdown metacharacters in scopes and messages
+    pub fn escape_markdown(mut self, escape_markdown: bool) -> Self {
+        self.escape_markdown = escape_markdown;
+        self
+    }
+
+    /// Drop commits whose subject matches any of these regexes
+    pub fn ignore_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignore_patterns = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Cache parsed commits so later runs don't parse them again. Without a
+    /// `cache_dir`, `build` keeps the cache inside the repository's git directory.
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Directory the commit cache is kept in; implies `cache(true)`
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = true;
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Mark signed commits with a lock after their message
+    pub fn show_signatures(mut self, show_signatures: bool) -> Self {
+        self.show_signatures = show_signatures;
+        self
+    }
+
+    /// Append each commit's date to its bullet, formatted like version dates
+    pub fn show_commit_dates(mut self, show_commit_dates: bool) -> Self {
+        self.show_commit_dates = show_commit_dates;
+        self
+    }
+
+    /// Cut bullet messages longer than `max` characters down to it at a word
+    /// boundary, ending with `…`. Markdown and HTML keep the full message as a
+    /// tooltip.
+    pub fn max_subject_length(mut self, max: usize) -> Self {
+        self.max_subject_length = Some(max);
+        self
+    }
+
+    /// Group the unreleased version's commits by the day or week they were
+    /// committed, newest first, with the type sections under each date
+    pub fn group_unreleased_by_date(mut self, grouping: DateGrouping) -> Self {
+        self.unreleased_date_grouping = Some(grouping);
+        self
+    }
+
+    /// Show dates in UTC instead of the offset they were recorded with
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.utc = utc;
+        self
+    }
+
+    /// URL version headings link to in HTML output. `{tag}` is replaced by the tag
+    /// name and `{remote}` by the web URL of the repository's remote.
+    pub fn tag_url_template(mut self, template: impl Into<String>) -> Self {
+        self.tag_url_template = template.into();
+        self
+    }
+
+    /// URL of a commit's page, the `{url}` of commit and document templates.
+    /// `{hash}` is replaced by the commit id, `{remote}` by the web URL of the
+    /// repository's remote and `{repo}` by its path on the host.
+    pub fn commit_url_template(mut self, template: impl Into<String>) -> Self {
+        self.commit_url_template = template.into();
+        self
+    }
+
+    /// URL of an author's profile, linked from author headings. `{login}` is
+    /// replaced by the author's login from `author_logins`; authors without one
+    /// stay plain text.
+    pub fn author_url_template(mut self, template: impl Into<String>) -> Self {
+        self.author_url_template = Some(template.into());
+        self
+    }
+
+    /// Logins of commit authors by author name, for `author_url_template`
+    pub fn author_logins(mut self, logins: HashMap<String, String>) -> Self {
+        self.author_logins = logins;
+        self
+    }
+
+    /// One-line summaries by version name (a tag name, or `unreleased`), shown in
+    /// italics under the version's heading
+    pub fn summaries(mut self, summaries: HashMap<String, String>) -> Self {
+        self.summaries = summaries;
+        self
+    }
+
+    /// Remote whose web URL links are built from, instead of `origin` (or the
+    /// first remote when there's no `origin`)
+    pub fn remote(mut self, name: impl Into<String>) -> Self {
+        self.remote = Some(name.into());
+        self
+    }
+
+    /// Leave the section of commits that didn't match the commit pattern out of the
+    /// rendered changelog, along with versions that only have such commits
+    pub fn hide_other(mut self, hide_other: bool) -> Self {
+        self.hide_other = hide_other;
+        self
+    }
+
+    /// Heading for the section of commits that didn't match the commit pattern
+    pub fn other_heading(mut self, heading: impl Into<String>) -> Self {
+        self.other_heading = Some(heading.into());
+        self
+    }
+
+    /// Translate section headings into this language, e.g. "es" or "fr-CA".
+    /// Languages and commit types without a translation stay in English.
+    pub fn locale(mut self, code: impl Into<String>) -> Self {
+        self.locale = Some(code.into());
+        self
+    }
+
+    /// Render these commit types in one section under `heading`. Can be called
+    /// several times for several merged sections.
+    pub fn merge_types(
+        mut self,
+        types: impl IntoIterator<Item = CommitType>,
+        heading: impl Into<String>,
+    ) -> Self {
+        self.type_merges.push(TypeMerge {
+            types: types.into_iter().collect(),
+            heading: heading.into(),
+        });
+        self
+    }
+
+    /// List the commits of `scope` in each section as one bullet reading
+    /// `summary`, with `{count}` replaced by their number, e.g.
+    /// "Updated {count} dependencies". Can be called several times for several
+    /// scopes.
+    pub fn collapse_scope(mut self, scope: impl Into<String>, summary: impl Into<String>) -> Self {
+        self.scope_collapses.push(ScopeCollapse {
+            scope: scope.into(),
+            summary: summary.into(),
+        });
+        self
+    }
+
+    /// Keep the emoji at the start of section headings
+    pub fn emoji(mut self, emoji: bool) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Open the markdown changelog with a section listing every breaking change,
+    /// linked to the version it landed in
+    pub fn breaking_summary(mut self, breaking_summary: bool) -> Self {
+        self.breaking_summary = breaking_summary;
+        self
+    }
+
+    /// Keep prerelease versions such as `v1.0.0-rc.1` (the default), leave them
+    /// out with their commits moving to the next stable version, or show only them
+    pub fn prerelease(mut self, prerelease: Prerelease) -> Self {
+        self.prerelease = prerelease;
+        self
+    }
+
+    /// Merge each prerelease version into the stable version released after it,
+    /// keeping its commits but not its section. Prereleases not followed by a
+    /// stable version yet keep their sections, unlike with
+    /// `Prerelease::Exclude` where their commits are unreleased.
+    pub fn group_prereleases_under_stable(mut self, group: bool) -> Self {
+        self.group_prereleases_under_stable = group;
+        self
+    }
+
+    /// Date versions by their annotated tag's tagger (the default) or by the
+    /// committer of the tagged commit. Lightweight tags always have the latter.
+    pub fn version_date_source(mut self, version_date_source: VersionDateSource) -> Self {
+        self.version_date_source = version_date_source;
+        self
+    }
+
+    /// Names of moving tags, such as `nightly` or `latest`, that are never treated
+    /// as versions even when they match the version pattern. Their commits stay
+    /// with the next version, or in the unreleased section.
+    pub fn rolling_tags<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rolling_tags = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Put the commits older than the earliest version tag in a version of their
+    /// own named `name`, e.g. `0.1.0`, instead of in the earliest tag's version.
+    /// Without any version tags everything stays unreleased.
+    pub fn initial_version(mut self, name: impl Into<String>) -> Self {
+        self.initial_version = Some(name.into());
+        self
+    }
+
+    /// Display versions by the version pattern's first capture group instead of
+    /// the full tag name, e.g. `1.2.3` for `v1.2.3`. Links still use the tag.
+    pub fn normalize_versions(mut self, normalize_versions: bool) -> Self {
+        self.normalize_versions = normalize_versions;
+        self
+    }
+
+    /// Render only the versions, leaving out the title, preamble and footer (and
+    /// for HTML the surrounding document), e.g. for release notes
+    pub fn notes_only(mut self, notes_only: bool) -> Self {
+        self.notes_only = notes_only;
+        self
+    }
+
+    /// HTML heading level of the title (1-6). Versions, type sections and scopes
+    /// follow one level deeper each.
+    pub fn heading_base(mut self, heading_base: u8) -> Self {
+        self.heading_base = heading_base.clamp(1, 6);
+        self
+    }
+
+    /// Write only the content for embedding into another document: HTML without
+    /// the `<!DOCTYPE>`, `<head>` and `<body>` around it, and markdown without
+    /// the title, preamble and footer, starting at the first version
+    pub fn fragment(mut self, fragment: bool) -> Self {
+        self.fragment = fragment;
+        self
+    }
+
+    /// Write compact HTML without indentation, line breaks between tags or
+    /// stylesheet whitespace
+    pub fn minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    /// Line ending of the written output; NDJSON always uses `\n`
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Start the output with a UTF-8 byte order mark, except for NDJSON
+    pub fn bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+
+    /// Open the repository at `repo_path` and build a generator for it
+    pub fn build(mut self, repo_path: &Path) -> Result<ChangelogGenerator<Git2Provider>> {
+        let git = Git2Provider::open(repo_path)?;
+        if self.cache && self.cache_dir.is_none() {
+            self.cache_dir = Some(git.git_dir().join(DEFAULT_CACHE_DIR_NAME));
+        }
+        self.build_with_provider(git)
+    }
+
+    /// Build a generator on top of an already opened provider
+    pub fn build_with_provider<P: GitProvider>(self, git: P) -> Result<ChangelogGenerator<P>> {
+        let version_regex = Regex::new(
+            self.version_pattern
+                .as_deref()
+                .unwrap_or(DEFAULT_VERSION_PATTERN),
+        )?;
+        let commit_pattern = self
+            .commit_pattern
+            .as_deref()
+            .unwrap_or(DEFAULT_COMMIT_PATTERN);
+        let commit_regex = Regex::new(commit_pattern)?;
+        let ignore_regexes = self
+            .ignore_patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let cache = self
+            .cache_dir
+            .map(|dir| CommitCache::load(&dir, commit_pattern, self.empty_as_other))
+            .transpose()?;
+
+        if self.require_full_history && git.is_shallow() {
+            return Err(GitError::ShallowClone.into());
+        }
+        let remote_url = git.remote_url(self.remote.as_deref())?;
+        let footer_describe = if self.describe_footer && self.footer {
+            Some(git.describe()?)
+        } else {
+            None
+        };
+        let headings = self
+            .locale
+            .as_deref()
+            .and_then(locale::find)
+            .map(|locale| {
+                CommitType::all()
+                    .iter()
+                    .filter_map(|commit_type| {
+                        Some((commit_type.clone(), locale.heading(commit_type)?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(ChangelogGenerator {
+            git,
+            version_regex,
+            commit_regex,
+            sort_order: self.sort_order,
+            date_format: self.date_format,
+            theme: self.theme,
+            footer: self.footer,
+            footer_timestamp: self.footer_timestamp,
+            footer_describe,
+            footer_marker: self.footer_marker,
+            scopes: self.scopes,
+            include_types: self.include_types,
+            exclude_types: self.exclude_types,
+            unreleased_requires: self.unreleased_requires,
+            group_by_scope: self.group_by_scope,
+            section_counts: self.section_counts,
+            no_scope_label: self.no_scope_label,
+            min_scope_size: self.min_scope_size,
+            small_scope_label: self.small_scope_label,
+            scope_sort: self.scope_sort,
+            scope_depth: self.scope_depth,
+            group_by: self.group_by,
+            layout: self.layout,
+            commit_template: self.commit_template,
+            document_template: self.document_template,
+            closed_issues: self.closed_issues,
+            empty_as_other: self.empty_as_other,
+            link_prs: self.link_prs,
+            relative_dates: self.relative_dates,
+            now: self.now.unwrap_or_else(Utc::now),
+            include_unreachable_tags: self.include_unreachable_tags,
+            since: self.since,
+            prerelease: self.prerelease,
+            group_prereleases_under_stable: self.group_prereleases_under_stable,
+            version_date_source: self.version_date_source,
+            rolling_tags: self.rolling_tags,
+            initial_version: self.initial_version,
+            limit: self.limit,
+            reverse_within_version: self.reverse_within_version,
+            jobs: self.jobs.max(1),
+            diffstat: self.diffstat,
+            show_paths: self.show_paths,
+            expand_body_bullets: self.expand_body_bullets,
+            max_paths: self.max_paths,
+            escape_markdown: self.escape_markdown,
+            ignore_regexes,
+            cache,
+            show_signatures: self.show_signatures,
+            show_commit_dates: self.show_commit_dates,
+            max_subject_length: self.max_subject_length,
+            commit_type_default: self.commit_type_default,
+            unreleased_date_grouping: self.unreleased_date_grouping,
+            utc: self.utc,
+            tag_url_template: self.tag_url_template,
+            commit_url_template: self.commit_url_template,
+            author_url_template: self.author_url_template,
+            author_logins: self.author_logins,
+            summaries: self.summaries,
+            remote_url,
+            hide_other: self.hide_other,
+            other_heading: self.other_heading,
+            headings,
+            type_merges: self.type_merges,
+            scope_collapses: self.scope_collapses,
+            emoji: self.emoji,
+            breaking_summary: self.breaking_summary,
+            normalize_versions: self.normalize_versions,
+            notes_only: self.notes_only,
+            heading_base: self.heading_base,
+            fragment: self.fragment,
+            minify: self.minify,
+            line_ending: self.line_ending,
+            bom: self.bom,
+        })
+    }
+}
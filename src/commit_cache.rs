@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+
+use crate::{
+    commit_type::CommitType,
+    json::{self, Value},
+    parsed_commit::ParsedCommit,
+};
+
+/// Name of the cache directory created inside the git directory by default
+pub const DEFAULT_CACHE_DIR_NAME: &str = "chronicle-cache";
+
+/// Name of the cache file inside the cache directory
+pub const CACHE_FILE_NAME: &str = "commits.ndjson";
+
+/// A parsed commit along with the subject line it was parsed from
+#[derive(Debug, Clone)]
+pub struct CachedCommit {
+    pub subject: String,
+    pub commit: ParsedCommit,
+}
+
+/// On-disk cache of parsed commits keyed by commit id, stored as one JSON object
+/// per line. The first line records the commit pattern the entries were parsed
+/// with and whether empty messages went to Other; a cache written with other
+/// settings is discarded when loaded.
+pub struct CommitCache {
+    dir: PathBuf,
+    commit_pattern: String,
+    empty_as_other: bool,
+    entries: RefCell<HashMap<String, CachedCommit>>,
+}
+
+impl CommitCache {
+    /// Load the cache from `dir`, starting empty when there is none yet
+    pub fn load(dir: &Path, commit_pattern: &str, empty_as_other: bool) -> io::Result<Self> {
+        let mut entries = HashMap::new();
+
+        match fs::read_to_string(dir.join(CACHE_FILE_NAME)) {
+            Ok(contents) => {
+                let mut lines = contents.lines();
+                let header = lines.next().and_then(json::parse_object);
+                // Caches from before the empty_as_other key parsed without it
+                let same_settings = header.is_some_and(|header| {
+                    header.get("commit_pattern") == Some(&Value::String(commit_pattern.to_string()))
+                        && header
+                            .get("empty_as_other")
+                            .map_or(!empty_as_other, |value| {
+                                *value == Value::Bool(empty_as_other)
+                            })
+                });
+
+                if same_settings {
+                    // Unreadable lines are skipped; those commits are simply parsed again
+                    for entry in lines.filter_map(parse_entry) {
+                        entries.insert(entry.commit.id.clone(), entry);
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            commit_pattern: commit_pattern.to_string(),
+            empty_as_other,
+            entries: RefCell::new(entries),
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<CachedCommit> {
+        self.entries.borrow().get(id).cloned()
+    }
+
+    pub fn insert(&self, entry: CachedCommit) {
+        self.entries
+            .borrow_mut()
+            .insert(entry.commit.id.clone(), entry);
+    }
+
+    /// Write the cache back to disk, dropping entries for commits that are not in
+    /// `commit_ids` anymore
+    pub fn save(&self, commit_ids: &HashSet<String>) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = io::BufWriter::new(fs::File::create(self.dir.join(CACHE_FILE_NAME))?);
+
+        writeln!(
+            &mut file,
+            r#"{{"commit_pattern":{},"empty_as_other":{}}}"#,
+            json::string(&self.commit_pattern),
+            self.empty_as_other
+        )?;
+
+        let entries = self.entries.borrow();
+        let mut ids: Vec<&String> = entries
+            .keys()
+            .filter(|id| commit_ids.contains(*id))
+            .collect();
+        ids.sort();
+
+        for id in ids {
+            writeln!(&mut file, "{}", entry_line(&entries[id]))?;
+        }
+
+        file.flush()
+    }
+}
+
+fn entry_line(entry: &CachedCommit) -> String {
+    let commit = &entry.commit;
+
+    format!(
+        r#"{{"id":{},"subject":{},"type":{},"scopes":{},"scope":{},"message":{},"timestamp":{},"signed":{},"breaking":{},"author":{},"closed_issues":{},"pr":{},"reverts":{},"body_items":{}}}"#,
+        json::string(&commit.id),
+        json::string(&entry.subject),
+        json::string(commit.commit_type.as_str()),
+        json::string_array(&commit.scopes),
+        commit
+            .raw_scope
+            .as_deref()
+            .map_or("null".to_string(), json::string),
+        json::string(&commit.message),
+        json::string(&commit.timestamp.to_rfc3339()),
+        commit.signed,
+        commit.breaking,
+        json::string(&commit.author),
+        json::string_array(
+            &commit
+                .closed_issues
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+        ),
+        commit
+            .pr
+            .map_or("null".to_string(), |pr| json::string(&pr.to_string())),
+        commit
+            .reverts
+            .as_deref()
+            .map_or("null".to_string(), json::string),
+        json::string_array(&commit.body_items)
+    )
+}
+
+fn parse_entry(line: &str) -> Option<CachedCommit> {
+    let mut object = json::parse_object(line)?;
+    let mut string = |key: &str| match object.remove(key) {
+        Some(Value::String(value)) => Some(value),
+        _ => None,
+    };
+
+    let id = string("id")?;
+    let subject = string("subject")?;
+    let commit_type = CommitType::from_prefix(&string("type")?);
+    let message = string("message")?;
+    let author = string("author")?;
+    let timestamp = DateTime::parse_from_rfc3339(&string("timestamp")?).ok()?;
+    let Some(Value::Array(scopes)) = object.remove("scopes") else {
+        return None;
+    };
+    // Entries from before the scope key render their scopes joined
+    let raw_scope = match object.remove("scope") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(scope)) => Some(scope),
+        _ => return None,
+    };
+    let Some(Value::Bool(signed)) = object.remove("signed") else {
+        return None;
+    };
+    let Some(Value::Bool(breaking)) = object.remove("breaking") else {
+        return None;
+    };
+    let Some(Value::Array(closed_issues)) = object.remove("closed_issues") else {
+        return None;
+    };
+    let closed_issues = closed_issues
+        .iter()
+        .map(|issue| issue.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let pr = match object.remove("pr") {
+        Some(Value::Null) => None,
+        Some(Value::String(pr)) => Some(pr.parse().ok()?),
+        _ => return None,
+    };
+    let reverts = match object.remove("reverts") {
+        Some(Value::Null) => None,
+        Some(Value::String(reverts)) => Some(reverts),
+        _ => return None,
+    };
+    let Some(Value::Array(body_items)) = object.remove("body_items") else {
+        return None;
+    };
+
+    Some(CachedCommit {
+        subject,
+        commit: ParsedCommit {
+            id,
+            commit_type,
+            scopes,
+            raw_scope,
+            message,
+            timestamp,
+            signed,
+            breaking,
+            author,
+            closed_issues,
+            pr,
+            reverts,
+            body_items,
+            paths: Vec::new(),
+        },
+    })
+}
@@ -0,0 +1,109 @@
+//! Bullet templates for `--commit-template`: plain text with `{name}`
+//! placeholders, and `{?...}` groups that are left out when any placeholder in
+//! them is empty, such as the bold scope and colon of the default template.
+
+/// Renders the same bullets as chronicle did before templates existed
+pub const DEFAULT_COMMIT_TEMPLATE: &str = "- {?**{scope}**: }{message}";
+
+/// Placeholders a template can use
+pub const PLACEHOLDERS: [&str; 8] = [
+    "scope",
+    "message",
+    "hash",
+    "short_hash",
+    "url",
+    "type",
+    "date",
+    "author",
+];
+
+/// Checks that every placeholder is known and every group is closed
+pub fn validate(template: &str) -> Result<(), String> {
+    let mut open_groups = 0;
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        let after = &rest[index + 1..];
+        if rest[index..].starts_with('}') {
+            // Closes a group; a stray '}' is plain text
+            open_groups -= usize::from(open_groups > 0);
+            rest = after;
+        } else if let Some(group) = after.strip_prefix('?') {
+            open_groups += 1;
+            rest = group;
+        } else {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unclosed '{{' in {:?}", template))?;
+            let name = &after[..end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "unknown placeholder {{{}}}; expected one of {}",
+                    name,
+                    PLACEHOLDERS.map(|name| format!("{{{}}}", name)).join(", ")
+                ));
+            }
+            rest = &after[end + 1..];
+        }
+    }
+
+    if open_groups > 0 {
+        return Err(format!("unclosed '{{?' group in {:?}", template));
+    }
+
+    Ok(())
+}
+
+/// Expands `template`, looking placeholders up with `field`. Empty fields expand
+/// to nothing and drop the `{?...}` group they are in.
+pub fn render(template: &str, field: &dyn Fn(&str) -> String) -> String {
+    render_group(template, field).0
+}
+
+// The expansion of `template` and whether all of its placeholders were non-empty
+fn render_group(template: &str, field: &dyn Fn(&str) -> String) -> (String, bool) {
+    let mut rendered = String::new();
+    let mut complete = true;
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        if let Some(group) = after.strip_prefix('?') {
+            let end = group_end(group).unwrap_or(group.len());
+            let (inner, inner_complete) = render_group(&group[..end], field);
+            if inner_complete {
+                rendered.push_str(&inner);
+            }
+            rest = group.get(end + 1..).unwrap_or("");
+        } else if let Some(end) = after.find('}') {
+            let value = field(&after[..end]);
+            complete &= !value.is_empty();
+            rendered.push_str(&value);
+            rest = &after[end + 1..];
+        } else {
+            rendered.push_str(&rest[start..]);
+            rest = "";
+        }
+    }
+    rendered.push_str(rest);
+
+    (rendered, complete)
+}
+
+// Index of the `}` closing a group whose contents start `group`, skipping the
+// placeholders inside it
+fn group_end(group: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in group.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(index),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
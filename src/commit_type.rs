@@ -1,3 +1,11 @@
+/// Commit types rendered together in one section under a custom heading, e.g.
+/// build, ci and chore as "Maintenance"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMerge {
+    pub types: Vec<CommitType>,
+    pub heading: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CommitType {
     Feature,
@@ -10,10 +18,29 @@ pub enum CommitType {
     Build,
     CI,
     Chore,
+    Revert,
     Other,
 }
 
 impl CommitType {
+    /// Every commit type, in the order their sections are rendered
+    pub fn all() -> &'static [CommitType] {
+        &[
+            CommitType::Feature,
+            CommitType::BugFix,
+            CommitType::Documentation,
+            CommitType::Style,
+            CommitType::Refactor,
+            CommitType::Performance,
+            CommitType::Testing,
+            CommitType::Build,
+            CommitType::CI,
+            CommitType::Chore,
+            CommitType::Revert,
+            CommitType::Other,
+        ]
+    }
+
     pub fn from_prefix(prefix: &str) -> Self {
         match prefix {
             "feat" => CommitType::Feature,
@@ -26,10 +53,38 @@ impl CommitType {
             "build" => CommitType::Build,
             "ci" => CommitType::CI,
             "chore" => CommitType::Chore,
+            "revert" => CommitType::Revert,
             _ => CommitType::Other,
         }
     }
 
+    /// The conventional prefix for this type, or "other" for unrecognized commits
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommitType::Feature => "feat",
+            CommitType::BugFix => "fix",
+            CommitType::Documentation => "doc",
+            CommitType::Style => "style",
+            CommitType::Refactor => "refactor",
+            CommitType::Performance => "perf",
+            CommitType::Testing => "test",
+            CommitType::Build => "build",
+            CommitType::CI => "ci",
+            CommitType::Chore => "chore",
+            CommitType::Revert => "revert",
+            CommitType::Other => "other",
+        }
+    }
+
+    /// Sort key placing a type's section, its position in `all`. Unrecognized
+    /// prefixes parse as `Other`, so their section comes last.
+    pub fn section_order(&self) -> usize {
+        CommitType::all()
+            .iter()
+            .position(|known| known == self)
+            .unwrap_or(CommitType::all().len())
+    }
+
     pub fn to_heading(&self) -> &'static str {
         match self {
             CommitType::Feature => "🚀 Features",
@@ -42,6 +97,7 @@ impl CommitType {
             CommitType::Build => "🏗️ Build",
             CommitType::CI => "👷 Continuous Integration",
             CommitType::Chore => "🧹 Chore",
+            CommitType::Revert => "⏪ Reverts",
             CommitType::Other => "Miscellaneous Tasks",
         }
     }
@@ -0,0 +1,143 @@
+//! Line-based unified diff, used by `--check` to show how a committed changelog
+//! differs from a freshly generated one.
+
+/// Lines of unchanged text shown around each change
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Unified diff turning `old` into `new`, with `old_label` and `new_label` in the
+/// `---`/`+++` header. Empty when both texts are identical.
+pub fn unified(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = (0..ops.len())
+        .filter(|&i| !matches!(ops[i], Op::Equal(_)))
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut index = 0;
+
+    while index < changes.len() {
+        // Changes whose context overlaps are shown in the same hunk
+        let first = changes[index];
+        while index + 1 < changes.len() && changes[index + 1] - changes[index] <= 2 * CONTEXT {
+            index += 1;
+        }
+        let last = changes[index];
+        index += 1;
+
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        diff.push_str(&hunk_header(&ops, start, end));
+
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                Op::Equal(line) => (' ', line),
+                Op::Delete(line) => ('-', line),
+                Op::Insert(line) => ('+', line),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
+
+// `@@ -start,len +start,len @@` for the ops in `start..end`
+fn hunk_header(ops: &[Op], start: usize, end: usize) -> String {
+    let old_before = ops[..start]
+        .iter()
+        .filter(|op| !matches!(op, Op::Insert(_)))
+        .count();
+    let new_before = ops[..start]
+        .iter()
+        .filter(|op| !matches!(op, Op::Delete(_)))
+        .count();
+    let old_len = ops[start..end]
+        .iter()
+        .filter(|op| !matches!(op, Op::Insert(_)))
+        .count();
+    let new_len = ops[start..end]
+        .iter()
+        .filter(|op| !matches!(op, Op::Delete(_)))
+        .count();
+
+    // An empty range is numbered after the line it follows, like diff does
+    let line = |before: usize, len: usize| if len == 0 { before } else { before + 1 };
+
+    format!(
+        "@@ -{},{} +{},{} @@\n",
+        line(old_before, old_len),
+        old_len,
+        line(new_before, new_len),
+        new_len
+    )
+}
+
+// Edit script from `old` to `new` based on their longest common subsequence.
+// The common prefix and suffix are matched up front to keep the table small.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j] is the LCS length of old_middle[i..] and new_middle[j..]
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0usize; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op> = old[..prefix].iter().map(|line| Op::Equal(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            ops.push(Op::Equal(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (j == new_middle.len() || lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            // Removed lines come before the lines replacing them
+            ops.push(Op::Delete(old_middle[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(new_middle[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Equal(line)));
+
+    ops
+}
@@ -0,0 +1,91 @@
+//! Whole-document templates for `--template`, rendered with Tera when chronicle
+//! is built with the `template` feature.
+//!
+//! The generator describes a document as a [`Context`] of text, flags and lists
+//! of nested contexts, which becomes the Tera context of the template. Values
+//! are inserted as they are, without escaping, since the template decides what
+//! kind of document it produces.
+
+/// A value in the template context
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "template"), allow(dead_code))]
+pub enum Value {
+    Text(String),
+    Bool(bool),
+    List(Vec<Context>),
+}
+
+/// Named values available to a template or one of its list items
+pub type Context = Vec<(&'static str, Value)>;
+
+// Name the template is registered under. Without an .html or .xml extension Tera
+// doesn't autoescape it.
+#[cfg(feature = "template")]
+const TEMPLATE_NAME: &str = "document";
+
+/// A parsed template, ready to render
+#[cfg(feature = "template")]
+#[derive(Debug)]
+pub struct Template {
+    tera: tera::Tera,
+}
+
+/// Without the `template` feature no template can be parsed, so there is never
+/// one to render
+#[cfg(not(feature = "template"))]
+#[derive(Debug)]
+pub struct Template(std::convert::Infallible);
+
+#[cfg(feature = "template")]
+impl Template {
+    /// Parse `source` as a Tera template, failing on syntax errors
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut tera = tera::Tera::new();
+        tera.add_raw_template(TEMPLATE_NAME, source)
+            .map_err(|err| err.to_string())?;
+        Ok(Self { tera })
+    }
+
+    /// Render the template with `context` as its variables. Fails when the
+    /// template uses a variable the context doesn't have.
+    pub fn render(&self, context: &Context) -> Result<String, String> {
+        let mut tera_context = tera::Context::new();
+        for (name, value) in context {
+            tera_context.insert_value(*name, tera_value(value));
+        }
+        self.tera
+            .render(TEMPLATE_NAME, &tera_context)
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(not(feature = "template"))]
+impl Template {
+    pub fn parse(_source: &str) -> Result<Self, String> {
+        Err("--template needs chronicle built with the template feature".to_string())
+    }
+
+    pub fn render(&self, _context: &Context) -> Result<String, String> {
+        match self.0 {}
+    }
+}
+
+#[cfg(feature = "template")]
+fn tera_value(value: &Value) -> tera::Value {
+    match value {
+        Value::Text(text) => tera::Value::from(text.as_str()),
+        Value::Bool(flag) => tera::Value::from(*flag),
+        Value::List(items) => tera::Value::from(
+            items
+                .iter()
+                .map(|item| {
+                    tera::Value::from(
+                        item.iter()
+                            .map(|(name, value)| (*name, tera_value(value)))
+                            .collect::<std::collections::HashMap<_, _>>(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
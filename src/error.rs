@@ -0,0 +1,35 @@
+//! The error type of a whole run, wrapping those of its parts so that callers can
+//! tell failures apart, e.g. a missing repository from an invalid pattern.
+
+use thiserror::Error;
+
+use crate::git_provider::GitError;
+
+#[derive(Error, Debug)]
+pub enum ChronicleError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid template {0}")]
+    InvalidTemplate(String),
+
+    #[error("Generated output is not valid UTF-8: {0}")]
+    InvalidOutput(#[from] std::string::FromUtf8Error),
+
+    /// Options that parse but don't make sense together with the repository
+    #[error("{0}")]
+    InvalidArguments(String),
+}
+
+// An invalid pattern is reported the same way whether the git provider or the
+// caller compiled it
+impl From<regex::Error> for ChronicleError {
+    fn from(err: regex::Error) -> Self {
+        ChronicleError::Git(GitError::from(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ChronicleError>;
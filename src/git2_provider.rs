@@ -1,43 +1,235 @@
 use std::path::Path;
 
-use chrono::{DateTime, TimeZone, Utc};
-use git2::{Repository, Sort, Time};
+use chrono::{DateTime, FixedOffset, TimeZone};
+use git2::{
+    DescribeFormatOptions, DescribeOptions, Diff, ErrorCode, Oid, Repository, Revwalk, Sort, Time,
+};
 
-use crate::git_provider::{CommitInfo, GitProvider, Result, TagInfo};
+use crate::{
+    git_provider::{CommitInfo, DiffStat, GitError, GitProvider, Result, TagInfo},
+    remote,
+};
 
 pub struct Git2Provider {
     repo: Repository,
 }
 
-impl GitProvider for Git2Provider {
-    fn open(path: &Path) -> Result<Self> {
-        let repo = Repository::open(path)?;
-        Ok(Self { repo })
+impl Git2Provider {
+    /// The repository's `.git` directory
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
     }
 
-    fn get_commit_ids(&self) -> Result<Vec<String>> {
+    // Walks the history from HEAD, newest first, leaving out `boundary` and its
+    // ancestors when given. An unborn HEAD (a repository without commits yet)
+    // walks nothing.
+    fn revwalk(&self, boundary: Option<&str>) -> Result<Revwalk<'_>> {
         let mut revwalk = self.repo.revwalk()?;
         revwalk.set_sorting(Sort::TIME)?;
-        revwalk.push_head()?;
 
-        let commit_ids = revwalk
+        match self.repo.head() {
+            Ok(_) => revwalk.push_head()?,
+            Err(err) if err.code() == ErrorCode::UnbornBranch => {}
+            Err(err) => return Err(err.into()),
+        }
+        if let Some(boundary) = boundary {
+            revwalk.hide(Oid::from_str(boundary)?)?;
+        }
+
+        Ok(revwalk)
+    }
+
+    fn commit_ids(&self, boundary: Option<&str>) -> Result<Vec<String>> {
+        let commit_ids = self
+            .revwalk(boundary)?
             .map(|oid_result| oid_result.map(|oid| oid.to_string()))
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(commit_ids)
     }
 
-    fn get_commit_info(&self, id: &str) -> Result<CommitInfo> {
-        let oid = git2::Oid::from_str(id)?;
+    fn walk_commits(
+        &self,
+        boundary: Option<&str>,
+    ) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        let revwalk = self.revwalk(boundary)?;
+
+        Ok(Box::new(
+            revwalk.map(move |oid_result| self.commit_info(oid_result?)),
+        ))
+    }
+
+    // A commit's change against its first parent, or against an empty tree for
+    // a root commit
+    fn first_parent_diff(&self, id: &str) -> Result<Diff<'_>> {
+        let commit = self.repo.find_commit(Oid::from_str(id)?)?;
+        let parent_tree = if commit.parent_count() > 0 {
+            Some(commit.parent(0)?.tree()?)
+        } else {
+            None
+        };
+
+        Ok(self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?)
+    }
+
+    fn commit_info(&self, oid: Oid) -> Result<CommitInfo> {
         let commit = self.repo.find_commit(oid)?;
 
         let message = commit.message().unwrap_or("").trim().to_string();
         let timestamp = git_time_to_datetime(&commit.time());
+        // libgit2 can only extract the signature, not verify it
+        let signed = self.repo.extract_signature(&oid, None).is_ok();
+        let author = commit.author().name().unwrap_or("").to_string();
 
         Ok(CommitInfo {
-            id: id.to_string(),
+            id: oid.to_string(),
             message,
             timestamp,
+            signed,
+            author,
+        })
+    }
+}
+
+impl GitProvider for Git2Provider {
+    fn open(path: &Path) -> Result<Self> {
+        // Like git itself, look for the repository in `path` and its ancestors
+        let repo = Repository::discover(path).map_err(|err| match err.code() {
+            ErrorCode::NotFound => GitError::RepositoryNotFound(path.display().to_string()),
+            _ => GitError::Git2Error(err),
+        })?;
+        Ok(Self { repo })
+    }
+
+    fn get_commit_ids(&self) -> Result<Vec<String>> {
+        self.commit_ids(None)
+    }
+
+    fn commits(&self) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        self.walk_commits(None)
+    }
+
+    // Hides the boundary from the walk, which also leaves out commits of branches
+    // merged before it
+    fn get_commit_ids_after(&self, boundary: &str) -> Result<Vec<String>> {
+        self.commit_ids(Some(boundary))
+    }
+
+    fn commits_after(
+        &self,
+        boundary: &str,
+    ) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        self.walk_commits(Some(boundary))
+    }
+
+    fn get_commit_info(&self, id: &str) -> Result<CommitInfo> {
+        let oid = Oid::from_str(id)?;
+        self.commit_info(oid)
+    }
+
+    fn diff_stat(&self, id: &str) -> Result<DiffStat> {
+        let stats = self.first_parent_diff(id)?.stats()?;
+
+        Ok(DiffStat {
+            files_changed: stats.files_changed(),
+            insertions: stats.insertions(),
+            deletions: stats.deletions(),
+        })
+    }
+
+    fn changed_paths(&self, id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .first_parent_diff(id)?
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    fn remote_url(&self, name: Option<&str>) -> Result<Option<String>> {
+        let remotes = self.repo.remotes()?;
+        let name = match name {
+            Some(name) if remotes.iter().flatten().any(|remote| remote == name) => Some(name),
+            Some(name) => return Err(GitError::RemoteNotFound(name.to_string())),
+            None if remotes.iter().flatten().any(|remote| remote == "origin") => Some("origin"),
+            None => remotes.iter().flatten().next(),
+        };
+
+        let Some(name) = name else {
+            return Ok(None);
+        };
+        let remote = self.repo.find_remote(name)?;
+
+        Ok(remote.url().and_then(remote::web_url))
+    }
+
+    fn is_shallow(&self) -> bool {
+        self.repo.is_shallow()
+    }
+
+    fn merge_base(&self, reference: &str) -> Result<String> {
+        let base = match self.repo.revparse_single(reference) {
+            Ok(object) => object.peel_to_commit()?.id(),
+            Err(err) if err.code() == ErrorCode::NotFound => {
+                return Err(GitError::ReferenceNotFound(reference.to_string()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let head = self.repo.head()?.peel_to_commit()?.id();
+
+        Ok(self.repo.merge_base(head, base)?.to_string())
+    }
+
+    fn describe(&self) -> Result<String> {
+        let describe = self.repo.describe(
+            DescribeOptions::new()
+                .describe_tags()
+                .show_commit_oid_as_fallback(true),
+        )?;
+
+        Ok(describe.format(Some(DescribeFormatOptions::new().abbreviated_size(7)))?)
+    }
+
+    fn describe_head(&self) -> Result<String> {
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            Err(err) if err.code() == ErrorCode::UnbornBranch => {
+                let head = self.repo.find_reference("HEAD")?;
+                let branch = head
+                    .symbolic_target()
+                    .map(|target| target.trim_start_matches("refs/heads/"))
+                    .unwrap_or("HEAD");
+                return Ok(format!("branch {} (no commits yet)", branch));
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let commit = head.peel_to_commit()?;
+        let short_id = commit.as_object().short_id()?;
+        let short_id = short_id.as_str().unwrap_or_default();
+
+        if head.is_branch() {
+            return Ok(format!(
+                "branch {} at {}",
+                head.shorthand().unwrap_or("HEAD"),
+                short_id
+            ));
+        }
+
+        // Detached, as in most CI checkouts; name the tag it sits on if any
+        let tag_names = self.repo.tag_names(None)?;
+        let tag = tag_names.iter().flatten().find(|name| {
+            self.repo
+                .revparse_single(&format!("refs/tags/{}", name))
+                .and_then(|obj| obj.peel_to_commit())
+                .is_ok_and(|tagged| tagged.id() == commit.id())
+        });
+
+        Ok(match tag {
+            Some(tag) => format!("detached HEAD at tag {} ({})", tag, short_id),
+            None => format!("detached HEAD at {}", short_id),
         })
     }
 
@@ -86,7 +278,9 @@ impl GitProvider for Git2Provider {
     }
 }
 
-// Helper function to convert git2::Time to chrono::DateTime<Utc>
-fn git_time_to_datetime(time: &Time) -> DateTime<Utc> {
-    Utc.timestamp_opt(time.seconds(), 0).unwrap()
+/// Converts a git2::Time to a chrono::DateTime, keeping its timezone offset
+pub fn git_time_to_datetime(time: &Time) -> DateTime<FixedOffset> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    offset.timestamp_opt(time.seconds(), 0).unwrap()
 }
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 use git2::Error as Git2Error;
 use thiserror::Error;
 
@@ -9,11 +9,46 @@ pub enum GitError {
     #[error("Git error: {0}")]
     Git2Error(#[from] Git2Error),
 
+    #[error("No git repository found in {0} or any of its parent directories")]
+    RepositoryNotFound(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("No remote named {0} in the repository")]
+    RemoteNotFound(String),
+
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+
     #[error("Invalid commit message: {0}")]
     InvalidCommitMessage(String),
 
     #[error("Invalid tag: {0}")]
     InvalidTag(String),
+
+    #[error("Invalid log format {0}")]
+    InvalidLogFormat(String),
+
+    #[error("Invalid git log input: {0}")]
+    InvalidLog(String),
+
+    #[error("No branch, tag or commit named {0}")]
+    ReferenceNotFound(String),
+
+    #[error("No tag named {0}")]
+    TagNotFound(String),
+
+    #[error("No tag matching the version pattern in the history, so there is no last release")]
+    NoVersionTag,
+
+    #[error("No {0} without a repository; git log input only has commits")]
+    Unavailable(&'static str),
+
+    #[error(
+        "The repository is a shallow clone, so its history is incomplete; fetch the rest with `git fetch --unshallow`"
+    )]
+    ShallowClone,
 }
 
 pub type Result<T> = std::result::Result<T, GitError>;
@@ -23,7 +58,48 @@ pub type Result<T> = std::result::Result<T, GitError>;
 pub struct CommitInfo {
     pub id: String,
     pub message: String,
-    pub timestamp: DateTime<Utc>,
+    /// Commit time in the committer's own timezone
+    pub timestamp: DateTime<FixedOffset>,
+    /// Whether the commit carries a GPG or SSH signature. The signature itself
+    /// is not checked against any key.
+    pub signed: bool,
+    /// Author name, empty when the commit doesn't record one
+    pub author: String,
+}
+
+/// Size of a change: files changed, lines added and lines removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffStat {
+    /// Adds another change's counts to these
+    pub fn add(&mut self, other: DiffStat) {
+        self.files_changed += other.files_changed;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+    }
+}
+
+impl std::fmt::Display for DiffStat {
+    /// e.g. "12 files changed, +340/-58"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} changed, +{}/-{}",
+            self.files_changed,
+            if self.files_changed == 1 {
+                "file"
+            } else {
+                "files"
+            },
+            self.insertions,
+            self.deletions
+        )
+    }
 }
 
 /// Tag details from the repository
@@ -31,7 +107,7 @@ pub struct CommitInfo {
 pub struct TagInfo {
     pub name: String,
     pub target_commit_id: String,
-    pub date: Option<DateTime<Utc>>,
+    pub date: Option<DateTime<FixedOffset>>,
 }
 
 /// A trait that abstracts Git operations needed for changelog generation
@@ -44,9 +120,64 @@ pub trait GitProvider {
     /// Get a list of all commit IDs in chronological order
     fn get_commit_ids(&self) -> Result<Vec<String>>;
 
+    /// Stream commit details in the same order as `get_commit_ids`, reading each
+    /// commit only when the iterator reaches it
+    fn commits(&self) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>>;
+
     /// Get details for a specific commit by ID
     fn get_commit_info(&self, id: &str) -> Result<CommitInfo>;
 
+    /// Like `get_commit_ids`, but stopping short of `boundary` and its ancestors.
+    /// The default suits a linear history and stops at `boundary` itself.
+    fn get_commit_ids_after(&self, boundary: &str) -> Result<Vec<String>> {
+        Ok(self
+            .get_commit_ids()?
+            .into_iter()
+            .take_while(|id| id != boundary)
+            .collect())
+    }
+
+    /// Like `commits`, but stopping short of `boundary` and its ancestors. The
+    /// default suits a linear history and stops at `boundary` itself.
+    fn commits_after(
+        &self,
+        boundary: &str,
+    ) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        let boundary = boundary.to_string();
+        Ok(Box::new(self.commits()?.take_while(move |commit| {
+            commit.as_ref().map_or(true, |commit| commit.id != boundary)
+        })))
+    }
+
+    /// Size of a commit's change against its first parent, or against an empty
+    /// tree for a root commit
+    fn diff_stat(&self, id: &str) -> Result<DiffStat>;
+
+    /// Paths of the files a commit changed against its first parent, or against
+    /// an empty tree for a root commit
+    fn changed_paths(&self, id: &str) -> Result<Vec<String>>;
+
+    /// Web URL of the remote called `name`, or without a name of `origin` (or
+    /// else the first remote), if it is hosted somewhere. A named remote that
+    /// doesn't exist is an error.
+    fn remote_url(&self, name: Option<&str>) -> Result<Option<String>>;
+
+    /// Whether the repository is a shallow clone, whose history stops at the
+    /// depth it was cloned with
+    fn is_shallow(&self) -> bool;
+
+    /// Describes where the history walk starts: the branch, tag or short commit
+    /// id HEAD resolves to
+    fn describe_head(&self) -> Result<String>;
+
+    /// HEAD as `git describe --tags` names it, e.g. `v1.2.0-5-gabc1234` five
+    /// commits after `v1.2.0`, or its short commit id when no tag precedes it
+    fn describe(&self) -> Result<String>;
+
+    /// ID of the best common ancestor of HEAD and `reference`, a branch, tag or
+    /// commit, where a branch forked off it
+    fn merge_base(&self, reference: &str) -> Result<String>;
+
     /// Get all tags that match a specific pattern with their target commit IDs
     fn get_tag_info(&self, version_pattern: &regex::Regex) -> Result<Vec<TagInfo>>;
 }
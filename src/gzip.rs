@@ -0,0 +1,36 @@
+//! Gzip output for `--gzip`, through flate2 when chronicle is built with the
+//! `gzip` feature.
+
+use std::path::{Path, PathBuf};
+
+/// Extension added to the names of compressed files
+pub const EXTENSION: &str = "gz";
+
+/// `path` with `.gz` added, e.g. "CHANGELOG.md.gz"
+pub fn path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+/// `data` as a gzip member, without a file name or modification time so the
+/// same input always gives the same bytes
+#[cfg(feature = "gzip")]
+pub fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Without the `gzip` feature there is no encoder, so `--gzip` fails before
+/// anything is written
+#[cfg(not(feature = "gzip"))]
+pub fn compress(_data: &[u8]) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "--gzip needs chronicle built with the gzip feature",
+    ))
+}
@@ -0,0 +1,22 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Name of the ignore file looked up in the repository root
+pub const IGNORE_FILE_NAME: &str = ".chronicleignore";
+
+/// Read the commit patterns listed in an ignore file
+pub fn load(path: &Path) -> io::Result<Vec<String>> {
+    Ok(parse(&fs::read_to_string(path)?))
+}
+
+/// One regex per line, matched against commit subjects. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn parse(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::DateTime;
+
+use crate::{
+    changelog_generator::VersionedCommit,
+    commit_type::CommitType,
+    git_provider::DiffStat,
+    json::{self, Value},
+    parsed_commit::ParsedCommit,
+    version::Version,
+};
+
+/// Read the versions back from a file of chronicle's NDJSON output
+pub fn load(path: &Path) -> io::Result<Vec<Version>> {
+    parse(&fs::read_to_string(path)?).map_err(|message| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), message),
+        )
+    })
+}
+
+/// Versions from NDJSON output, one commit per line, in the order their first
+/// commit appears. Each commit goes to its type's bucket of the version it names,
+/// in file order, and its diff stat is added to the version's. Blank lines are
+/// skipped.
+pub fn parse(contents: &str) -> Result<Vec<Version>, String> {
+    let mut versions: Vec<Version> = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (
+            VersionedCommit {
+                version: name,
+                version_date: date,
+                commit,
+            },
+            diff_stat,
+        ) = parse_line(line)
+            .ok_or_else(|| format!("line {} is not a commit of NDJSON output", index + 1))?;
+
+        let position = match versions.iter().position(|version| version.name == name) {
+            Some(position) => position,
+            None => {
+                versions.push(Version {
+                    name,
+                    date,
+                    commits_by_type: HashMap::new(),
+                    diff_stat: None,
+                });
+                versions.len() - 1
+            }
+        };
+        if let Some(diff_stat) = diff_stat {
+            versions[position]
+                .diff_stat
+                .get_or_insert_default()
+                .add(diff_stat);
+        }
+        versions[position]
+            .commits_by_type
+            .entry(commit.commit_type.clone())
+            .or_default()
+            .push(commit);
+    }
+
+    Ok(versions)
+}
+
+// A line's commit with its version, and the commit's diff stat
+fn parse_line(line: &str) -> Option<(VersionedCommit, Option<DiffStat>)> {
+    let mut object = json::parse_object(line)?;
+    let mut string = |key: &str| match object.remove(key) {
+        Some(Value::String(value)) => Some(value),
+        _ => None,
+    };
+
+    let version = string("version")?;
+    let id = string("id")?;
+    let commit_type = CommitType::from_prefix(&string("type")?);
+    let message = string("message")?;
+    let author = string("author")?;
+    let timestamp = DateTime::parse_from_rfc3339(&string("timestamp")?).ok()?;
+    let version_date = match object.remove("version_date") {
+        Some(Value::Null) => None,
+        Some(Value::String(date)) => Some(DateTime::parse_from_rfc3339(&date).ok()?),
+        _ => return None,
+    };
+    let Some(Value::Array(scopes)) = object.remove("scopes") else {
+        return None;
+    };
+    // Hand-written lines may leave out the scope as written
+    let raw_scope = match object.remove("scope") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(scope)) => Some(scope),
+        _ => return None,
+    };
+    let Some(Value::Bool(signed)) = object.remove("signed") else {
+        return None;
+    };
+    let Some(Value::Bool(breaking)) = object.remove("breaking") else {
+        return None;
+    };
+    let Some(Value::Array(closed_issues)) = object.remove("closed_issues") else {
+        return None;
+    };
+    let closed_issues = closed_issues
+        .iter()
+        .map(|issue| issue.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    let pr = match object.remove("pr") {
+        Some(Value::Null) => None,
+        Some(Value::Number(pr)) => Some(pr),
+        _ => return None,
+    };
+    // Output from before these keys, or edited by hand, may leave them out
+    let reverts = match object.remove("reverts") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(reverts)) => Some(reverts),
+        _ => return None,
+    };
+    let mut strings = |key: &str| match object.remove(key) {
+        None => Some(Vec::new()),
+        Some(Value::Array(values)) => Some(values),
+        _ => None,
+    };
+    let body_items = strings("body_items")?;
+    let paths = strings("paths")?;
+    let mut count = |key: &str| match object.remove(key) {
+        None | Some(Value::Null) => Some(None),
+        Some(Value::Number(count)) => Some(Some(count as usize)),
+        _ => None,
+    };
+    let diff_stat = match (
+        count("files_changed")?,
+        count("insertions")?,
+        count("deletions")?,
+    ) {
+        (Some(files_changed), Some(insertions), Some(deletions)) => Some(DiffStat {
+            files_changed,
+            insertions,
+            deletions,
+        }),
+        (None, None, None) => None,
+        _ => return None,
+    };
+
+    Some((
+        VersionedCommit {
+            version,
+            version_date,
+            commit: ParsedCommit {
+                id,
+                commit_type,
+                scopes,
+                raw_scope,
+                message,
+                timestamp,
+                signed,
+                breaking,
+                author,
+                closed_issues,
+                pr,
+                reverts,
+                body_items,
+                paths,
+            },
+        },
+        diff_stat,
+    ))
+}
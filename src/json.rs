@@ -0,0 +1,193 @@
+//! Just enough JSON for chronicle's line-oriented files: writing string literals
+//! and reading back flat objects whose values are strings, arrays of strings or
+//! whole numbers, whole numbers, booleans or null.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A value of a flat JSON object
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    String(String),
+    /// A non-negative whole number
+    Number(u64),
+    /// Array items are strings, or whole numbers kept as their digits
+    Array(Vec<String>),
+}
+
+/// Quote and escape `value` as a JSON string literal
+pub fn string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            // The line and paragraph separators are line breaks to YAML 1.1
+            // readers, which also read these literals
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                json.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+/// Format `values` as a JSON array of strings
+pub fn string_array(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| string(value)).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// Parse a single-line object such as `{"a":"b","c":["d"],"e":null}`. Returns
+/// None for anything malformed or nested deeper than a string array.
+pub fn parse_object(line: &str) -> Option<HashMap<String, Value>> {
+    let mut chars = line.trim().chars().peekable();
+    let mut object = HashMap::new();
+
+    expect(&mut chars, '{')?;
+    if skip_whitespace(&mut chars) == Some('}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            expect(&mut chars, ':')?;
+            let value = parse_value(&mut chars)?;
+            object.insert(key, value);
+
+            match skip_whitespace(&mut chars) {
+                Some(',') => {
+                    chars.next();
+                }
+                Some('}') => {
+                    chars.next();
+                    break;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    skip_whitespace(&mut chars).is_none().then_some(object)
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Option<Value> {
+    match skip_whitespace(chars)? {
+        '"' => parse_string(chars).map(Value::String),
+        '[' => {
+            chars.next();
+            let mut items = Vec::new();
+            if skip_whitespace(chars) == Some(']') {
+                chars.next();
+                return Some(Value::Array(items));
+            }
+            loop {
+                match skip_whitespace(chars)? {
+                    '0'..='9' => items.push(parse_digits(chars)),
+                    _ => items.push(parse_string(chars)?),
+                }
+                match skip_whitespace(chars)? {
+                    ',' => {
+                        chars.next();
+                    }
+                    ']' => {
+                        chars.next();
+                        return Some(Value::Array(items));
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        'n' => parse_literal(chars, "null", Value::Null),
+        't' => parse_literal(chars, "true", Value::Bool(true)),
+        'f' => parse_literal(chars, "false", Value::Bool(false)),
+        '0'..='9' => parse_digits(chars).parse().ok().map(Value::Number),
+        _ => None,
+    }
+}
+
+fn parse_literal(chars: &mut Peekable<Chars>, literal: &str, value: Value) -> Option<Value> {
+    for expected in literal.chars() {
+        expect(chars, expected)?;
+    }
+    Some(value)
+}
+
+fn parse_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    expect(chars, '"')?;
+    let mut value = String::new();
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                '/' => value.push('/'),
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'u' => value.push(parse_escaped_char(chars)?),
+                _ => return None,
+            },
+            c => value.push(c),
+        }
+    }
+}
+
+// The character of a `\uXXXX` escape, after the `u`. Characters outside the
+// Basic Multilingual Plane are escaped as a surrogate pair, which is two escapes.
+fn parse_escaped_char(chars: &mut Peekable<Chars>) -> Option<char> {
+    let unit = parse_hex4(chars)?;
+    if !(0xd800..0xdc00).contains(&unit) {
+        return char::from_u32(unit);
+    }
+
+    expect(chars, '\\')?;
+    expect(chars, 'u')?;
+    let low = parse_hex4(chars)?;
+    if !(0xdc00..0xe000).contains(&low) {
+        return None;
+    }
+    char::from_u32(0x10000 + ((unit - 0xd800) << 10) + (low - 0xdc00))
+}
+
+fn parse_hex4(chars: &mut Peekable<Chars>) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+    if hex.len() != 4 {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+// Skips whitespace and returns the next character without consuming it
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> Option<char> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.peek().copied()
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Option<()> {
+    chars.next_if_eq(&expected).map(|_| ())
+}
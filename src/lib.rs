@@ -0,0 +1,26 @@
+//! Conventional commit parsing as used by chronicle, for other tools that want
+//! to read commit messages the same way without generating a changelog.
+//!
+//! [`parser::parse`] parses a commit message on its own, while
+//! [`parser::parse_commit`] also carries over the id, date and author of a
+//! [`git_provider::CommitInfo`] read from a repository. Failures of a run are
+//! reported as a [`ChronicleError`].
+//!
+//! ```
+//! use chronicle::parser::{self, DEFAULT_COMMIT_PATTERN};
+//! use regex::Regex;
+//!
+//! let regex = Regex::new(DEFAULT_COMMIT_PATTERN).unwrap();
+//! let commit = parser::parse("feat(api): add endpoint\n\nCloses #12", &regex);
+//! assert_eq!(commit.scopes, ["api"]);
+//! assert_eq!(commit.closed_issues, [12]);
+//! ```
+
+pub mod commit_type;
+pub mod error;
+pub mod git_provider;
+pub mod parsed_commit;
+pub mod parser;
+
+pub use error::ChronicleError;
+pub use parsed_commit::ParsedCommit;
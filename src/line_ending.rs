@@ -0,0 +1,58 @@
+//! Line endings and byte order mark for `--line-ending` and `--bom`. The writers
+//! only ever write `\n`; this wrapper turns it into the configured line ending on
+//! the way out.
+
+use std::io::{self, Write};
+
+use crate::LineEnding;
+
+/// UTF-8 byte order mark, which some Windows tools need to detect the encoding
+pub const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Writes through to `inner`, turning every `\n` that doesn't already follow a
+/// `\r` into the configured line ending
+pub struct LineEndingWriter<W: Write> {
+    inner: W,
+    line_ending: LineEnding,
+    // Whether the last byte written was a `\r`, which may end an earlier chunk
+    after_cr: bool,
+}
+
+impl<W: Write> LineEndingWriter<W> {
+    /// Wraps `inner`, writing the byte order mark first when `bom` is set
+    pub fn new(mut inner: W, line_ending: LineEnding, bom: bool) -> io::Result<Self> {
+        if bom {
+            inner.write_all(BOM)?;
+        }
+
+        Ok(Self {
+            inner,
+            line_ending,
+            after_cr: false,
+        })
+    }
+}
+
+impl<W: Write> Write for LineEndingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.line_ending == LineEnding::Lf || buf.is_empty() {
+            return self.inner.write(buf);
+        }
+
+        let mut converted = Vec::with_capacity(buf.len() + buf.len() / 32);
+        for &byte in buf {
+            if byte == b'\n' && !self.after_cr {
+                converted.push(b'\r');
+            }
+            converted.push(byte);
+            self.after_cr = byte == b'\r';
+        }
+        self.inner.write_all(&converted)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,95 @@
+//! Translated section headings for `--locale`. Each locale is a table of
+//! heading texts by commit type; the emoji of the English headings are kept.
+
+use crate::commit_type::CommitType;
+
+/// A language's section headings
+pub struct Locale {
+    /// Language code, e.g. "es"
+    pub code: &'static str,
+    headings: &'static [(CommitType, &'static str)],
+}
+
+/// The built-in locales. A new one only needs an entry here; commit types it
+/// leaves out keep their English heading.
+pub const LOCALES: &[Locale] = &[
+    Locale {
+        code: "de",
+        headings: &[
+            (CommitType::Feature, "Funktionen"),
+            (CommitType::BugFix, "Fehlerbehebungen"),
+            (CommitType::Documentation, "Dokumentation"),
+            (CommitType::Style, "Stil"),
+            (CommitType::Refactor, "Refactoring"),
+            (CommitType::Performance, "Leistung"),
+            (CommitType::Testing, "Tests"),
+            (CommitType::Build, "Build"),
+            (CommitType::CI, "Kontinuierliche Integration"),
+            (CommitType::Chore, "Wartung"),
+            (CommitType::Revert, "Rücknahmen"),
+            (CommitType::Other, "Sonstige Aufgaben"),
+        ],
+    },
+    Locale {
+        code: "es",
+        headings: &[
+            (CommitType::Feature, "Funcionalidades"),
+            (CommitType::BugFix, "Correcciones de errores"),
+            (CommitType::Documentation, "Documentación"),
+            (CommitType::Style, "Estilo"),
+            (CommitType::Refactor, "Refactorización"),
+            (CommitType::Performance, "Rendimiento"),
+            (CommitType::Testing, "Pruebas"),
+            (CommitType::Build, "Compilación"),
+            (CommitType::CI, "Integración continua"),
+            (CommitType::Chore, "Tareas"),
+            (CommitType::Revert, "Reversiones"),
+            (CommitType::Other, "Tareas varias"),
+        ],
+    },
+    Locale {
+        code: "fr",
+        headings: &[
+            (CommitType::Feature, "Fonctionnalités"),
+            (CommitType::BugFix, "Corrections de bugs"),
+            (CommitType::Documentation, "Documentation"),
+            (CommitType::Style, "Style"),
+            (CommitType::Refactor, "Refactorisation"),
+            (CommitType::Performance, "Performances"),
+            (CommitType::Testing, "Tests"),
+            (CommitType::Build, "Construction"),
+            (CommitType::CI, "Intégration continue"),
+            (CommitType::Chore, "Maintenance"),
+            (CommitType::Revert, "Annulations"),
+            (CommitType::Other, "Tâches diverses"),
+        ],
+    },
+];
+
+/// The locale for a code such as "fr", "fr-CA" or "fr_FR", matched on its
+/// language; None for languages without translations
+pub fn find(code: &str) -> Option<&'static Locale> {
+    let language = code.split(['-', '_']).next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|locale| locale.code.eq_ignore_ascii_case(language))
+}
+
+impl Locale {
+    /// The heading of a commit type's section in this language, with the emoji
+    /// of the English heading, or None when the type isn't translated
+    pub fn heading(&self, commit_type: &CommitType) -> Option<String> {
+        let (_, text) = self
+            .headings
+            .iter()
+            .find(|(translated, _)| translated == commit_type)?;
+        let english = commit_type.to_heading();
+
+        Some(match english.split_once(' ') {
+            Some((emoji, _)) if !emoji.chars().any(char::is_alphanumeric) => {
+                format!("{} {}", emoji, text)
+            }
+            _ => text.to_string(),
+        })
+    }
+}
@@ -1,12 +1,16 @@
 use regex::Regex;
+use std::collections::HashMap;
 use std::path::Path;
 
-use crate::git_provider::{CommitInfo, GitError, GitProvider, Result, TagInfo};
+use crate::git_provider::{CommitInfo, DiffStat, GitError, GitProvider, Result, TagInfo};
 
 #[derive(Default)]
 pub struct MockGitProvider {
     pub commits: Vec<CommitInfo>,
     pub tags: Vec<TagInfo>,
+    pub remote_url: Option<String>,
+    pub diff_stats: HashMap<String, DiffStat>,
+    pub changed_paths: HashMap<String, Vec<String>>,
 }
 
 impl MockGitProvider {
@@ -14,6 +18,9 @@ impl MockGitProvider {
         Self {
             commits: Vec::new(),
             tags: Vec::new(),
+            remote_url: None,
+            diff_stats: HashMap::new(),
+            changed_paths: HashMap::new(),
         }
     }
 
@@ -26,6 +33,26 @@ impl MockGitProvider {
         self.tags = tags;
         self
     }
+
+    pub fn with_remote_url(mut self, remote_url: &str) -> Self {
+        self.remote_url = Some(remote_url.to_string());
+        self
+    }
+
+    /// Fake the size of a commit's change; other commits change nothing
+    pub fn with_diff_stat(mut self, id: &str, diff_stat: DiffStat) -> Self {
+        self.diff_stats.insert(id.to_string(), diff_stat);
+        self
+    }
+
+    /// Fake the files a commit changed; other commits change none
+    pub fn with_changed_paths(mut self, id: &str, paths: &[&str]) -> Self {
+        self.changed_paths.insert(
+            id.to_string(),
+            paths.iter().map(|path| path.to_string()).collect(),
+        );
+        self
+    }
 }
 
 impl GitProvider for MockGitProvider {
@@ -37,6 +64,10 @@ impl GitProvider for MockGitProvider {
         Ok(self.commits.iter().map(|c| c.id.clone()).collect())
     }
 
+    fn commits(&self) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        Ok(Box::new(self.commits.iter().cloned().map(Ok)))
+    }
+
     fn get_commit_info(&self, id: &str) -> Result<CommitInfo> {
         self.commits
             .iter()
@@ -45,6 +76,55 @@ impl GitProvider for MockGitProvider {
             .ok_or_else(|| GitError::InvalidCommitMessage(format!("Commit not found: {}", id)))
     }
 
+    fn diff_stat(&self, id: &str) -> Result<DiffStat> {
+        Ok(self.diff_stats.get(id).copied().unwrap_or_default())
+    }
+
+    fn changed_paths(&self, id: &str) -> Result<Vec<String>> {
+        Ok(self.changed_paths.get(id).cloned().unwrap_or_default())
+    }
+
+    fn remote_url(&self, _name: Option<&str>) -> Result<Option<String>> {
+        Ok(self.remote_url.clone())
+    }
+
+    fn is_shallow(&self) -> bool {
+        false
+    }
+
+    fn describe_head(&self) -> Result<String> {
+        Ok("HEAD".to_string())
+    }
+
+    // The nearest tag walking back from the first commit, as git describe would
+    fn describe(&self) -> Result<String> {
+        let head = self.commits.first().ok_or(GitError::Unavailable("HEAD"))?;
+        let short_id = &head.id[..head.id.len().min(7)];
+        for (distance, commit) in self.commits.iter().enumerate() {
+            if let Some(tag) = self
+                .tags
+                .iter()
+                .find(|tag| tag.target_commit_id == commit.id)
+            {
+                return Ok(match distance {
+                    0 => tag.name.clone(),
+                    _ => format!("{}-{}-g{}", tag.name, distance, short_id),
+                });
+            }
+        }
+
+        Ok(short_id.to_string())
+    }
+
+    // The history is a single line, so a commit on it is its own merge base
+    fn merge_base(&self, reference: &str) -> Result<String> {
+        self.commits
+            .iter()
+            .find(|c| c.id == reference)
+            .map(|c| c.id.clone())
+            .ok_or_else(|| GitError::ReferenceNotFound(reference.to_string()))
+    }
+
     fn get_tag_info(&self, version_pattern: &Regex) -> Result<Vec<TagInfo>> {
         Ok(self
             .tags
@@ -1,11 +1,45 @@
 use crate::commit_type::CommitType;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParsedCommit {
     pub id: String,
     pub commit_type: CommitType,
-    pub scope: Option<String>,
+    pub scopes: Vec<String>,
+    /// The scope as written in the subject, e.g. `ui,core`, which `scope` renders
+    pub raw_scope: Option<String>,
     pub message: String,
-    pub timestamp: DateTime<Utc>,
+    pub timestamp: DateTime<FixedOffset>,
+    pub signed: bool,
+    /// Marked with `!` after the type or a `BREAKING CHANGE:` footer
+    pub breaking: bool,
+    pub author: String,
+    /// Issues named in `Closes #12`-style footers
+    pub closed_issues: Vec<u64>,
+    /// Pull request number of a squash merge, taken off the end of the message
+    pub pr: Option<u64>,
+    /// Id of the commit this one reverts, from a `This reverts commit <sha>.`
+    /// line in the body as `git revert` writes it
+    pub reverts: Option<String>,
+    /// Items of a `- ` list in the commit body
+    pub body_items: Vec<String>,
+    /// Top-level directories and files the commit changed, filled in with
+    /// `show_paths` and never cached
+    pub paths: Vec<String>,
+}
+
+impl ParsedCommit {
+    /// The scope as written in the subject, or the scopes joined with "," when
+    /// that isn't known. None for scope-less commits.
+    pub fn scope(&self) -> Option<String> {
+        if self.scopes.is_empty() {
+            None
+        } else {
+            Some(
+                self.raw_scope
+                    .clone()
+                    .unwrap_or_else(|| self.scopes.join(",")),
+            )
+        }
+    }
 }
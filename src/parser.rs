@@ -0,0 +1,241 @@
+//! Commit message parsing, independent of any repository or generator.
+//!
+//! The commit regex is expected to provide the named groups `type`, `scope`
+//! (optional), `breaking` (optional, the `!` of `feat!:`) and `message`.
+//! Subjects that don't match are returned as `CommitType::Other` with the whole
+//! subject as the message.
+
+use chrono::{DateTime, FixedOffset};
+use regex::Regex;
+
+use crate::{commit_type::CommitType, git_provider::CommitInfo, parsed_commit::ParsedCommit};
+
+/// Commit regex for conventional commit subjects such as `feat(api)!: message`
+pub const DEFAULT_COMMIT_PATTERN: &str =
+    r"^(?P<type>\w+)(?:\((?P<scope>.+)\))?(?P<breaking>!)?:\s(?P<message>.+)$";
+
+/// Parse a commit's subject line with `regex`, keeping its id, date, signature
+/// and author
+pub fn parse_commit(commit: &CommitInfo, regex: &Regex) -> ParsedCommit {
+    ParsedCommit {
+        id: commit.id.clone(),
+        timestamp: commit.timestamp,
+        signed: commit.signed,
+        author: commit.author.clone(),
+        ..parse(&commit.message, regex)
+    }
+}
+
+/// A commit whose pattern matched with an empty message (e.g. a bare `feat:`,
+/// with a pattern that allows one) as an Other commit with its whole subject as
+/// the message. Other commits are returned unchanged.
+pub fn empty_as_other(mut commit: ParsedCommit, subject: &str) -> ParsedCommit {
+    if commit.commit_type != CommitType::Other && commit.message.trim().is_empty() {
+        commit.commit_type = CommitType::Other;
+        commit.scopes.clear();
+        commit.raw_scope = None;
+        commit.message = subject.to_string();
+        commit.pr = None;
+    }
+
+    commit
+}
+
+/// Parse `commits` with `regex` on `jobs` threads, in the order given. With one
+/// job, or without the `parallel` feature, they are parsed one at a time.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+pub fn parse_commits(commits: &[CommitInfo], regex: &Regex, jobs: usize) -> Vec<ParsedCommit> {
+    #[cfg(feature = "parallel")]
+    if jobs > 1 {
+        use rayon::prelude::*;
+
+        // Without a pool of its own the parsing still runs, just not in parallel
+        if let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(jobs).build() {
+            return pool.install(|| {
+                commits
+                    .par_iter()
+                    .map(|commit| parse_commit(commit, regex))
+                    .collect()
+            });
+        }
+    }
+
+    commits
+        .iter()
+        .map(|commit| parse_commit(commit, regex))
+        .collect()
+}
+
+/// Parse a full commit message, subject line and body, with `regex`. Nothing
+/// about the commit itself is known, so the id and author are empty, the date is
+/// the Unix epoch and the commit is unsigned.
+pub fn parse(full_message: &str, regex: &Regex) -> ParsedCommit {
+    let message = subject(full_message);
+    let id = String::new();
+    let timestamp = DateTime::<FixedOffset>::default();
+    let signed = false;
+    let author = String::new();
+    let breaking_footer = has_breaking_footer(full_message);
+    let closed_issues = closed_issues(full_message);
+    let reverts = reverted_commit(full_message);
+    let body_items = body_items(full_message);
+
+    if let Some(captures) = regex.captures(message) {
+        let commit_type = CommitType::from_prefix(captures.name("type").map_or("", |m| m.as_str()));
+        let raw_scope = captures.name("scope").map(|m| m.as_str().to_string());
+        let scopes = raw_scope.as_deref().map(split_scopes).unwrap_or_default();
+        let (message, pr) = split_pull_request(captures.name("message").map_or("", |m| m.as_str()));
+        let breaking = captures.name("breaking").is_some() || breaking_footer;
+
+        ParsedCommit {
+            id,
+            commit_type,
+            scopes,
+            raw_scope,
+            message: message.to_string(),
+            timestamp,
+            signed,
+            breaking,
+            author,
+            closed_issues,
+            pr,
+            reverts,
+            body_items,
+            paths: Vec::new(),
+        }
+    } else {
+        let (message, pr) = split_pull_request(message);
+
+        ParsedCommit {
+            id,
+            commit_type: CommitType::Other,
+            scopes: Vec::new(),
+            raw_scope: None,
+            message: message.to_string(),
+            timestamp,
+            signed,
+            breaking: breaking_footer,
+            author,
+            closed_issues,
+            pr,
+            reverts,
+            body_items,
+            paths: Vec::new(),
+        }
+    }
+}
+
+/// Splits the ` (#123)` that squash merges append to a subject off a message,
+/// returning the rest of the message and the pull request number. Messages that
+/// don't end in exactly that are returned whole.
+pub fn split_pull_request(message: &str) -> (&str, Option<u64>) {
+    let pr = message
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" (#"))
+        .filter(|(_, number)| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|(rest, number)| Some((rest.trim_end(), number.parse().ok()?)));
+
+    match pr {
+        Some((rest, number)) if !rest.is_empty() => (rest, Some(number)),
+        _ => (message, None),
+    }
+}
+
+/// The first line of a commit message, trimmed
+pub fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or("").trim()
+}
+
+/// Whether the body of a commit message has a `BREAKING CHANGE:` (or
+/// `BREAKING-CHANGE:`) footer
+pub fn has_breaking_footer(message: &str) -> bool {
+    message
+        .lines()
+        .skip(1)
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"))
+}
+
+/// The items of `- ` lists in the body of a commit message, in order. Nested
+/// items are listed like top-level ones.
+pub fn body_items(message: &str) -> Vec<String> {
+    message
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.trim_start().strip_prefix("- "))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The commit id named by a `This reverts commit <sha>.` line in the body of a
+/// commit message, as written by `git revert` under both the `revert: <subject>`
+/// and the `Revert "<subject>"` subjects. The id must be at least 7 hex digits.
+pub fn reverted_commit(message: &str) -> Option<String> {
+    message.lines().skip(1).find_map(|line| {
+        let sha = line
+            .trim()
+            .strip_prefix("This reverts commit ")?
+            .trim_end_matches('.');
+        let is_sha = (7..=40).contains(&sha.len()) && sha.bytes().all(|b| b.is_ascii_hexdigit());
+        is_sha.then(|| sha.to_lowercase())
+    })
+}
+
+// Footer keywords that mark an issue as resolved by the commit
+const CLOSING_KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+/// Issue numbers referenced by `Closes #12, #15`, `Fixes: #20` or `Resolves #3`
+/// lines in the body of a commit message, in the order they appear. Keywords are
+/// case-insensitive. Only the list of `#N` references right after the keyword
+/// counts, so prose such as "Fixed a crash reported in #42" closes nothing.
+pub fn closed_issues(message: &str) -> Vec<u64> {
+    let mut issues = Vec::new();
+
+    for line in message.lines().skip(1) {
+        let Some((keyword, references)) = line
+            .trim()
+            .split_once(|c: char| c == ':' || c.is_whitespace())
+        else {
+            continue;
+        };
+        if !CLOSING_KEYWORDS.contains(&keyword.to_lowercase().as_str()) {
+            continue;
+        }
+
+        let references = references
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|reference| !reference.is_empty());
+        for reference in references {
+            let Some(number) = reference.strip_prefix('#') else {
+                break;
+            };
+            // A sentence may end right after the last reference
+            let (number, last) = match number.strip_suffix('.') {
+                Some(number) => (number, true),
+                None => (number, false),
+            };
+            let Ok(number) = number.parse::<u64>() else {
+                break;
+            };
+            issues.push(number);
+            if last {
+                break;
+            }
+        }
+    }
+
+    issues
+}
+
+/// Split a captured scope like "api, ui" into its individual scopes
+pub fn split_scopes(scope: &str) -> Vec<String> {
+    scope
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
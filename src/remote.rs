@@ -0,0 +1,107 @@
+/// Default tag link, matching the release pages of GitHub and Gitea
+pub const DEFAULT_TAG_URL_TEMPLATE: &str = "{remote}/releases/tag/{tag}";
+
+/// Default commit link, which GitHub, GitLab, Gitea and Forgejo all serve
+pub const DEFAULT_COMMIT_URL_TEMPLATE: &str = "{remote}/commit/{hash}";
+
+const ISSUE_URL_TEMPLATE: &str = "{remote}/issues/{number}";
+const PULL_REQUEST_URL_TEMPLATE: &str = "{remote}/pull/{number}";
+
+/// Turn a remote's fetch URL into the web URL of the repository, e.g.
+/// `git@github.com:owner/repo.git` into `https://github.com/owner/repo`.
+/// Returns None for local paths and other URLs without a host.
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+
+    let (host, path) = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+        .or_else(|| remote.strip_prefix("ssh://"))
+        .or_else(|| remote.strip_prefix("git://"))
+    {
+        rest.split_once('/')?
+    } else {
+        // scp-like syntax: [user@]host:path
+        let (host, path) = remote.split_once(':')?;
+        if host.contains('/') {
+            return None;
+        }
+        (host, path)
+    };
+
+    // Drop credentials or the ssh user, and the ssh port
+    let host = host.rsplit('@').next()?;
+    let host = if remote.starts_with("ssh://") {
+        host.split(':').next()?
+    } else {
+        host
+    };
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{}/{}", host, path))
+}
+
+/// Issue page for `number` on the repository's web URL, e.g.
+/// `https://github.com/owner/repo/issues/12`
+pub fn issue_url(remote: Option<&str>, number: u64) -> Option<String> {
+    fill(
+        ISSUE_URL_TEMPLATE,
+        remote,
+        &[("number", &number.to_string())],
+    )
+}
+
+/// Pull request page for `number` on the repository's web URL, e.g.
+/// `https://github.com/owner/repo/pull/34`
+pub fn pull_request_url(remote: Option<&str>, number: u64) -> Option<String> {
+    fill(
+        PULL_REQUEST_URL_TEMPLATE,
+        remote,
+        &[("number", &number.to_string())],
+    )
+}
+
+/// Fill in a tag URL template, `{tag}` being the tag name
+pub fn tag_url(template: &str, remote: Option<&str>, tag: &str) -> Option<String> {
+    fill(template, remote, &[("tag", tag)])
+}
+
+/// Fill in a commit URL template, `{hash}` being the full commit id
+pub fn commit_url(template: &str, remote: Option<&str>, hash: &str) -> Option<String> {
+    fill(template, remote, &[("hash", hash)])
+}
+
+/// Fill in an author URL template, `{login}` being the author's login
+pub fn author_url(template: &str, remote: Option<&str>, login: &str) -> Option<String> {
+    fill(template, remote, &[("login", login)])
+}
+
+// Replaces `{remote}` by the repository's web URL, `{repo}` by its path on the
+// host (e.g. `owner/repo`) and each `{name}` of `values` by its value. None if
+// the template needs a remote and there is none.
+fn fill(template: &str, remote: Option<&str>, values: &[(&str, &str)]) -> Option<String> {
+    let mut url = template.to_string();
+    if url.contains("{remote}") || url.contains("{repo}") {
+        let remote = remote?;
+        url = url
+            .replace("{remote}", remote)
+            .replace("{repo}", repo_path(remote));
+    }
+
+    for (name, value) in values {
+        url = url.replace(&format!("{{{}}}", name), value);
+    }
+    Some(url)
+}
+
+// The path of a web URL after its host, or the whole URL if it has no scheme
+fn repo_path(remote: &str) -> &str {
+    remote
+        .split_once("://")
+        .and_then(|(_, rest)| rest.split_once('/'))
+        .map_or(remote, |(_, path)| path)
+}
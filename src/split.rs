@@ -0,0 +1,89 @@
+//! Per-version output for `--split`: one file per version and format, described
+//! by an `index.json` manifest that docs sites can build their navigation from.
+//! Also the per-type slicing of `--per-type-files`.
+
+use std::collections::HashMap;
+
+use crate::{
+    OutputFormat, changelog_generator::strip_emoji, commit_type::CommitType, json, version::Version,
+};
+
+/// Name of the manifest written next to the per-version files
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// File a version is written to in `format`, e.g. "v1.0.0.md". Path separators in
+/// tag names such as "release/1.0" become hyphens.
+pub fn file_name(version: &str, format: OutputFormat) -> String {
+    format!(
+        "{}.{}",
+        version.replace(['/', '\\'], "-"),
+        format.extension()
+    )
+}
+
+/// The manifest for `versions`, kept in the order given: an array with one object
+/// per version holding its name, date (null when unreleased or untagged), the
+/// file written for each format and its number of commits
+pub fn index(versions: &[&Version], formats: &[OutputFormat]) -> String {
+    let entries: Vec<String> = versions
+        .iter()
+        .map(|version| {
+            let files: Vec<String> = formats
+                .iter()
+                .map(|format| {
+                    format!(
+                        "{}:{}",
+                        json::string(format.name()),
+                        json::string(&file_name(&version.name, *format))
+                    )
+                })
+                .collect();
+
+            format!(
+                r#"  {{"name":{},"date":{},"files":{{{}}},"commits":{}}}"#,
+                json::string(&version.name),
+                version
+                    .date
+                    .map_or("null".to_string(), |date| json::string(&date.to_rfc3339())),
+                files.join(","),
+                version.commit_count()
+            )
+        })
+        .collect();
+
+    if entries.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+}
+
+/// File a commit type's commits are written to with `--per-type-files`, named
+/// after its English heading, e.g. "bug-fixes.md"
+pub fn type_file_name(commit_type: &CommitType, format: OutputFormat) -> String {
+    format!(
+        "{}.{}",
+        strip_emoji(commit_type.to_heading())
+            .to_lowercase()
+            .replace(' ', "-"),
+        format.extension()
+    )
+}
+
+/// The versions cut down to their commits of `commit_type`, in the order given,
+/// leaving out those without any. Diff stats are dropped since they sum up the
+/// commits of every type.
+pub fn versions_of_type(versions: &[&Version], commit_type: &CommitType) -> Vec<Version> {
+    versions
+        .iter()
+        .filter_map(|version| {
+            let commits = version.commits_by_type.get(commit_type)?;
+            Some(Version {
+                name: version.name.clone(),
+                date: version.date,
+                commits_by_type: HashMap::from([(commit_type.clone(), commits.clone())]),
+                diff_stat: None,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,269 @@
+//! Commits read from `git log` output instead of a repository, for `-r -` on
+//! runners where chronicle can't open the repository itself.
+
+use std::io::Read;
+use std::path::Path;
+
+use chrono::DateTime;
+use regex::Regex;
+
+use crate::git_provider::{CommitInfo, DiffStat, GitError, GitProvider, Result, TagInfo};
+
+/// Pretty format read by default, to be passed on to `git log --format`: the
+/// commit id, committer date, author name, signature status and raw message
+pub const DEFAULT_LOG_FORMAT: &str = "%H%x1f%cI%x1f%an%x1f%G?%x1f%B%x1e";
+
+// Placeholders in a format are separated by unit separators, and every commit
+// ends with a record separator
+const FIELD_SEPARATOR: &str = "%x1f";
+const RECORD_SEPARATOR: &str = "%x1e";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    Date,
+    Author,
+    Signature,
+    Message,
+}
+
+impl Field {
+    fn from_placeholder(placeholder: &str) -> Option<Self> {
+        match placeholder {
+            "%H" => Some(Field::Id),
+            "%cI" | "%aI" => Some(Field::Date),
+            "%an" => Some(Field::Author),
+            "%G?" => Some(Field::Signature),
+            "%B" | "%s" => Some(Field::Message),
+            _ => None,
+        }
+    }
+}
+
+pub struct StdinProvider {
+    commits: Vec<CommitInfo>,
+    tags: Vec<TagInfo>,
+}
+
+impl StdinProvider {
+    /// Parse the output of `git log --format=<format>`, newest commit first.
+    /// The format needs `%H`, a strict ISO date (`%cI` or `%aI`) and a message
+    /// (`%B` or `%s`), and may add `%an` and `%G?`, in any order.
+    pub fn parse(log: &str, format: &str) -> Result<Self> {
+        let fields = parse_format(format)?;
+        let commits = log
+            .split('\x1e')
+            // git log puts a newline between the records
+            .map(|record| record.trim_start_matches(['\n', '\r']))
+            .filter(|record| !record.trim().is_empty())
+            .map(|record| parse_record(record, &fields))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            commits,
+            tags: Vec::new(),
+        })
+    }
+
+    /// Read `git log --format=<format>` output from stdin
+    pub fn from_stdin(format: &str) -> Result<Self> {
+        let mut log = String::new();
+        std::io::stdin().read_to_string(&mut log)?;
+        Self::parse(&log, format)
+    }
+
+    /// Use these tags. Those without a date get the date of their commit, as
+    /// lightweight tags do when read from a repository.
+    pub fn with_tags(mut self, tags: Vec<TagInfo>) -> Self {
+        self.tags = tags
+            .into_iter()
+            .map(|mut tag| {
+                if tag.date.is_none() {
+                    tag.date = self
+                        .commits
+                        .iter()
+                        .find(|commit| commit.id == tag.target_commit_id)
+                        .map(|commit| commit.timestamp);
+                }
+                tag
+            })
+            .collect();
+        self
+    }
+}
+
+/// Parse a tag list with one `NAME COMMIT [DATE]` line per tag, the date in
+/// RFC 3339. Blank lines are skipped.
+pub fn parse_tags(input: &str) -> Result<Vec<TagInfo>> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(commit), date, None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Err(GitError::InvalidTag(format!(
+                    "expected NAME COMMIT [DATE], got '{}'",
+                    line
+                )));
+            };
+            let date = date
+                .map(|date| {
+                    DateTime::parse_from_rfc3339(date).map_err(|_| {
+                        GitError::InvalidTag(format!("invalid date '{}' for {}", date, name))
+                    })
+                })
+                .transpose()?;
+
+            Ok(TagInfo {
+                name: name.to_string(),
+                target_commit_id: commit.to_string(),
+                date,
+            })
+        })
+        .collect()
+}
+
+// The fields of a pretty format in the order they appear
+fn parse_format(format: &str) -> Result<Vec<Field>> {
+    let invalid = |reason: String| GitError::InvalidLogFormat(format!("{}: {}", format, reason));
+
+    let body = format
+        .strip_suffix(RECORD_SEPARATOR)
+        .ok_or_else(|| invalid(format!("must end with {}", RECORD_SEPARATOR)))?;
+    let fields = body
+        .split(FIELD_SEPARATOR)
+        .map(|placeholder| {
+            Field::from_placeholder(placeholder).ok_or_else(|| {
+                invalid(format!(
+                    "unknown placeholder '{}'; expected %H, %cI, %aI, %an, %G?, %B or %s separated by {}",
+                    placeholder, FIELD_SEPARATOR
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for (required, name) in [
+        (Field::Id, "%H"),
+        (Field::Date, "%cI or %aI"),
+        (Field::Message, "%B or %s"),
+    ] {
+        match fields.iter().filter(|field| **field == required).count() {
+            0 => return Err(invalid(format!("needs {}", name))),
+            1 => {}
+            _ => return Err(invalid(format!("has {} more than once", name))),
+        }
+    }
+
+    Ok(fields)
+}
+
+// A commit from one record of the log, its values in the order of `fields`
+fn parse_record(record: &str, fields: &[Field]) -> Result<CommitInfo> {
+    // The last field takes the rest, in case a message holds a separator
+    let values: Vec<&str> = record.splitn(fields.len(), '\x1f').collect();
+    if values.len() != fields.len() {
+        return Err(GitError::InvalidLog(format!(
+            "expected {} fields, got {} in {:?}",
+            fields.len(),
+            values.len(),
+            record
+        )));
+    }
+
+    let mut commit = CommitInfo {
+        id: String::new(),
+        message: String::new(),
+        timestamp: DateTime::UNIX_EPOCH.fixed_offset(),
+        signed: false,
+        author: String::new(),
+    };
+    for (field, value) in fields.iter().zip(values) {
+        match field {
+            Field::Id => commit.id = value.trim().to_string(),
+            Field::Date => {
+                commit.timestamp = DateTime::parse_from_rfc3339(value.trim()).map_err(|_| {
+                    GitError::InvalidLog(format!("invalid date '{}'", value.trim()))
+                })?;
+            }
+            Field::Author => commit.author = value.trim().to_string(),
+            // Anything but N means the commit carries a signature, checked or not
+            Field::Signature => commit.signed = !matches!(value.trim(), "" | "N"),
+            Field::Message => commit.message = value.trim().to_string(),
+        }
+    }
+
+    if commit.id.is_empty() {
+        return Err(GitError::InvalidLog(format!(
+            "missing commit id in {:?}",
+            record
+        )));
+    }
+
+    Ok(commit)
+}
+
+impl GitProvider for StdinProvider {
+    fn open(_path: &Path) -> Result<Self> {
+        Self::from_stdin(DEFAULT_LOG_FORMAT)
+    }
+
+    fn get_commit_ids(&self) -> Result<Vec<String>> {
+        Ok(self.commits.iter().map(|c| c.id.clone()).collect())
+    }
+
+    fn commits(&self) -> Result<Box<dyn Iterator<Item = Result<CommitInfo>> + '_>> {
+        Ok(Box::new(self.commits.iter().cloned().map(Ok)))
+    }
+
+    fn get_commit_info(&self, id: &str) -> Result<CommitInfo> {
+        self.commits
+            .iter()
+            .find(|c| c.id == id)
+            .cloned()
+            .ok_or_else(|| GitError::InvalidCommitMessage(format!("Commit not found: {}", id)))
+    }
+
+    fn diff_stat(&self, _id: &str) -> Result<DiffStat> {
+        Err(GitError::Unavailable("diff stats"))
+    }
+
+    fn changed_paths(&self, _id: &str) -> Result<Vec<String>> {
+        Err(GitError::Unavailable("changed paths"))
+    }
+
+    // There are no remotes, so only asking for a particular one fails
+    fn remote_url(&self, name: Option<&str>) -> Result<Option<String>> {
+        match name {
+            Some(name) => Err(GitError::RemoteNotFound(name.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    // Whatever history git log printed is all there is to go on
+    fn is_shallow(&self) -> bool {
+        false
+    }
+
+    fn describe_head(&self) -> Result<String> {
+        Ok("git log output on stdin".to_string())
+    }
+
+    fn describe(&self) -> Result<String> {
+        Err(GitError::Unavailable("git describe"))
+    }
+
+    fn merge_base(&self, _reference: &str) -> Result<String> {
+        Err(GitError::Unavailable("merge base"))
+    }
+
+    fn get_tag_info(&self, version_pattern: &Regex) -> Result<Vec<TagInfo>> {
+        Ok(self
+            .tags
+            .iter()
+            .filter(|t| version_pattern.is_match(&t.name))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Name of the summaries file looked up in the repository root
+pub const SUMMARIES_FILE_NAME: &str = "chronicle-summaries.toml";
+
+/// Read the version summaries listed in a summaries file
+pub fn load(path: &Path) -> io::Result<HashMap<String, String>> {
+    parse(&fs::read_to_string(path)?)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// One `"v1.2.0" = "Summary"` per line, mapping a version's tag name (or
+/// `unreleased`) to the line shown under its heading. This is the subset of TOML
+/// needed for that: keys are quoted or bare, values are basic strings with the
+/// usual escapes or literal strings in single quotes, and blank lines and `#`
+/// comments are skipped.
+pub fn parse(contents: &str) -> Result<HashMap<String, String>, String> {
+    let mut summaries = HashMap::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("expected '\"version\" = \"summary\"', got '{}'", line);
+
+        let (key, rest) = match line.chars().next() {
+            Some(quote @ ('"' | '\'')) => string(line, quote).ok_or_else(invalid)?,
+            _ => {
+                let end = line.find(['=', ' ', '\t']).unwrap_or(line.len());
+                (line[..end].to_string(), &line[end..])
+            }
+        };
+        let rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or_else(invalid)?
+            .trim_start();
+        let (value, rest) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => string(rest, quote).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        let rest = rest.trim_start();
+        if key.is_empty() || !(rest.is_empty() || rest.starts_with('#')) {
+            return Err(invalid());
+        }
+
+        summaries.insert(key, value);
+    }
+
+    Ok(summaries)
+}
+
+// Reads the string starting at the `quote` that `text` begins with, returning its
+// value and the text after it. Single quotes take the text as it is; double
+// quotes take backslash escapes.
+fn string(text: &str, quote: char) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            _ if c == quote => return Some((value, &text[index + 1..])),
+            '\\' if quote == '"' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                _ => return None,
+            },
+            _ => value.push(c),
+        }
+    }
+
+    None
+}
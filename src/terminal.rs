@@ -0,0 +1,118 @@
+//! ANSI colors and paging for previewing the changelog in a terminal with `-o -`.
+
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[1;36m";
+const YELLOW: &str = "\x1b[1;33m";
+
+/// Whether stdout output should be colored: only on a terminal, and neither with
+/// `--no-color` nor with a non-empty `NO_COLOR` environment variable
+pub fn use_color(no_color: bool) -> bool {
+    !no_color
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+/// Colors the headings of a markdown changelog: versions cyan, type sections
+/// yellow, the title and scope groups bold and the footer comment dimmed
+pub fn colorize_markdown(markdown: &str) -> String {
+    let mut colored = String::with_capacity(markdown.len());
+
+    for line in markdown.split_inclusive('\n') {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        let color = if text.starts_with("#### ") || text.starts_with("# ") {
+            Some(BOLD)
+        } else if text.starts_with("### ") {
+            Some(YELLOW)
+        } else if text.starts_with("## ") {
+            Some(CYAN)
+        } else if text.starts_with("<!-- ") {
+            Some(DIM)
+        } else {
+            None
+        };
+
+        match color {
+            Some(color) => {
+                colored.push_str(&format!("{}{}{}{}", color, text, RESET, newline));
+            }
+            None => colored.push_str(line),
+        }
+    }
+
+    colored
+}
+
+/// The pager command to run: `$PAGER`, or `less` when it isn't set. None when
+/// paging is turned off with an empty `PAGER` or `PAGER=cat`.
+pub fn pager_command(pager_env: Option<&str>) -> Option<&str> {
+    match pager_env.map(str::trim) {
+        None => Some("less"),
+        Some("" | "cat") => None,
+        Some(command) => Some(command),
+    }
+}
+
+/// The pager stdout output should go through: only on a terminal and without
+/// `--no-pager`. None as well when the pager can't be started, so output falls
+/// back to plain stdout.
+pub fn pager(no_pager: bool) -> Option<Pager> {
+    if no_pager || !io::stdout().is_terminal() {
+        return None;
+    }
+    let pager_env = std::env::var("PAGER").ok();
+    Pager::spawn(pager_command(pager_env.as_deref())?)
+}
+
+/// A running pager fed the output through its stdin
+pub struct Pager {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Pager {
+    /// Starts `command`, split on whitespace into the program and its arguments.
+    /// `less` is told to pass colors through and to exit when the output fits on
+    /// one screen, unless `LESS` is set.
+    pub fn spawn(command: &str) -> Option<Pager> {
+        let mut words = command.split_whitespace();
+        let mut pager = Command::new(words.next()?);
+        pager.args(words).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            pager.env("LESS", "FRX");
+        }
+
+        let mut child = pager.spawn().ok()?;
+        let stdin = child.stdin.take();
+        Some(Pager { child, stdin })
+    }
+
+    /// Writes to the pager. Once it has been quit the rest of the output is
+    /// dropped instead of failing with a broken pipe.
+    pub fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(stdin) = &mut self.stdin else {
+            return Ok(());
+        };
+        match stdin.write_all(bytes) {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Closes the pager's input and waits for it to be quit
+    pub fn wait(mut self) -> io::Result<()> {
+        drop(self.stdin.take());
+        self.child.wait()?;
+        Ok(())
+    }
+}
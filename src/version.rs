@@ -1,11 +1,45 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset};
 use std::collections::HashMap;
 
-use crate::{commit_type::CommitType, parsed_commit::ParsedCommit};
+use crate::{commit_type::CommitType, git_provider::DiffStat, parsed_commit::ParsedCommit};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Version {
     pub name: String,
-    pub date: Option<DateTime<Utc>>,
+    pub date: Option<DateTime<FixedOffset>>,
     pub commits_by_type: HashMap<CommitType, Vec<ParsedCommit>>,
+    /// Combined size of the version's commits, only counted with `--diffstat`
+    pub diff_stat: Option<DiffStat>,
+}
+
+/// Whether a tag name is a semver prerelease such as "v1.0.0-rc.1": a
+/// MAJOR.MINOR.PATCH version after any non-numeric prefix, followed by a hyphen
+/// and dot-separated identifiers. Build metadata ("1.0.0+build.5") alone doesn't
+/// make a prerelease.
+pub fn is_prerelease(name: &str) -> bool {
+    let Some(start) = name.find(|c: char| c.is_ascii_digit()) else {
+        return false;
+    };
+    let version = &name[start..];
+    let version = version
+        .split_once('+')
+        .map_or(version, |(version, _)| version);
+    let Some((core, prerelease)) = version.split_once('-') else {
+        return false;
+    };
+
+    let numeric = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let identifier = |part: &str| {
+        !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    };
+    core.split('.').count() == 3
+        && core.split('.').all(numeric)
+        && prerelease.split('.').all(identifier)
+}
+
+impl Version {
+    /// Total number of commits across all type buckets
+    pub fn commit_count(&self) -> usize {
+        self.commits_by_type.values().map(Vec::len).sum()
+    }
 }